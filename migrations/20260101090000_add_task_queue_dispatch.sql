@@ -0,0 +1,6 @@
+-- Dispatch parameters for tasks parked in the `queued` status by
+-- PODUP_MAX_CONCURRENT_TASKS. JSON shaped like
+-- {"type":"manual","action":"manual-trigger"} or
+-- {"type":"github-webhook","runner_unit":"webhook-task-..."}.
+ALTER TABLE tasks
+ADD COLUMN queued_dispatch TEXT;
@@ -0,0 +1,16 @@
+-- Single-row snapshot of the scheduler loop. The `scheduler` subcommand runs
+-- in its own process, so it records every tick here for the settings API.
+CREATE TABLE IF NOT EXISTS scheduler_state (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    -- Unix seconds when the current scheduler process started its loop.
+    started_at INTEGER NOT NULL,
+    pid INTEGER,
+    iteration INTEGER NOT NULL,
+    interval_secs INTEGER NOT NULL,
+    last_tick_at INTEGER NOT NULL,
+    -- NULL once the loop stopped (e.g. --max-iterations reached).
+    next_tick_at INTEGER,
+    last_task_id TEXT,
+    -- Error from the most recent tick, NULL when it dispatched cleanly.
+    last_error TEXT
+);
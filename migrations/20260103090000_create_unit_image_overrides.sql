@@ -0,0 +1,8 @@
+-- Desired image pinned per unit via the manual services API. When a row
+-- exists it takes precedence over the Image= line in the unit file.
+CREATE TABLE IF NOT EXISTS unit_image_overrides (
+    unit TEXT PRIMARY KEY,
+    image TEXT NOT NULL,
+    -- Unix seconds of the last PUT.
+    updated_at INTEGER NOT NULL
+);
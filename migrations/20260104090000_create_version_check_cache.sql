@@ -0,0 +1,10 @@
+-- Last successful GitHub latest-release lookup. Each HTTP request runs in its
+-- own process, so /api/version/check keeps the cache here to stay within the
+-- unauthenticated GitHub API quota.
+CREATE TABLE IF NOT EXISTS version_check_cache (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    release_tag TEXT NOT NULL,
+    published_at TEXT,
+    -- Unix seconds when the release was fetched from GitHub.
+    fetched_at INTEGER NOT NULL
+);
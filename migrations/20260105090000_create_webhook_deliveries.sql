@@ -0,0 +1,12 @@
+-- GitHub delivery ids that already produced a task. GitHub redelivers at
+-- least once, so a repeated X-GitHub-Delivery within the idempotency window
+-- answers with the original task instead of queueing another one.
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    delivery TEXT PRIMARY KEY,
+    task_id TEXT NOT NULL,
+    -- Unix seconds when the delivery was first accepted.
+    received_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_received_at
+    ON webhook_deliveries (received_at);
@@ -0,0 +1,9 @@
+-- Admin-toggled read-only mode. Every request runs in its own process, so the
+-- toggle lives here rather than in memory. PODUP_READ_ONLY overrides it.
+CREATE TABLE IF NOT EXISTS maintenance_mode (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    enabled INTEGER NOT NULL DEFAULT 0,
+    reason TEXT,
+    -- Unix seconds of the last toggle.
+    updated_at INTEGER NOT NULL
+);
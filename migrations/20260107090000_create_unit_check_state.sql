@@ -0,0 +1,8 @@
+-- Last time the scheduler dispatched a real verification for each unit. Used
+-- by PODUP_UNIT_MAX_STALE_SECS to force a check on units whose digests never
+-- appear to change.
+CREATE TABLE IF NOT EXISTS unit_check_state (
+    unit TEXT PRIMARY KEY,
+    -- Unix seconds of the last scheduler dispatch covering this unit.
+    last_checked_at INTEGER NOT NULL
+);
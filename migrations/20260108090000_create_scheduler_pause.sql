@@ -0,0 +1,9 @@
+-- Admin pause switch for the scheduler loop. The scheduler runs in its own
+-- process and reads this row at the top of every tick.
+CREATE TABLE IF NOT EXISTS scheduler_pause (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    paused INTEGER NOT NULL DEFAULT 0,
+    reason TEXT,
+    -- Unix seconds of the last pause/resume.
+    updated_at INTEGER NOT NULL
+);
@@ -0,0 +1,10 @@
+-- Free-form tags (`prod`, `env=staging`) attached to tasks for grouping and
+-- filtering the task list.
+CREATE TABLE IF NOT EXISTS task_tags (
+    task_id TEXT NOT NULL,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (task_id, tag),
+    FOREIGN KEY (task_id) REFERENCES tasks (task_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_tags_tag ON task_tags (tag);
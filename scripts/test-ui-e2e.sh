@@ -38,6 +38,7 @@ cd "$repo_root"
 echo "[ui-e2e] starting http-server on 127.0.0.1:25211"
 : >"$http_log"
 PODUP_SKIP_PODMAN="1" \
+PODUP_SKIP_SYSTEMD="1" \
 PODUP_ENV="test" \
 PODUP_STATE_DIR="$state_dir" \
 PODUP_DB_URL="sqlite://$state_dir/pod-upgrade-trigger.db" \
@@ -55,6 +56,7 @@ server_pid_main=$!
 echo "[ui-e2e] starting auth http-server on 127.0.0.1:25212"
 : >"$auth_http_log"
 PODUP_SKIP_PODMAN="1" \
+PODUP_SKIP_SYSTEMD="1" \
 PODUP_ENV="test" \
 PODUP_STATE_DIR="$auth_state_dir" \
 PODUP_DB_URL="sqlite://$auth_state_dir/pod-upgrade-trigger.db" \
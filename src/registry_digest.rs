@@ -1,6 +1,6 @@
 use base64::Engine;
 use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
-use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT};
 use reqwest::{Client, StatusCode};
 use serde_json::Value;
 use sqlx::{Row, SqlitePool};
@@ -8,15 +8,32 @@ use std::collections::HashMap;
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use url::Url;
 
 const AUTH_JSON_REL_PATH: &str = ".config/containers/auth.json";
 const DOCKER_CONTENT_DIGEST_HEADER: &str = "docker-content-digest";
+/// A refresh makes a handful of 3s-timeout requests; a flight row older than
+/// this was left by a process that died mid-refresh.
+const REFRESH_FLIGHT_STALE_SECS: i64 = 30;
+const REFRESH_FLIGHT_POLL: Duration = Duration::from_millis(100);
 
 pub(crate) const ENV_REGISTRY_DIGEST_CACHE_TTL_SECS: &str = "PODUP_REGISTRY_DIGEST_CACHE_TTL_SECS";
 pub(crate) const DEFAULT_REGISTRY_DIGEST_CACHE_TTL_SECS: u64 = 600;
 const ENV_REGISTRY_DIGEST_MOCK: &str = "PODUP_REGISTRY_DIGEST_MOCK";
+const ENV_REGISTRY_LABELS_MOCK: &str = "PODUP_REGISTRY_LABELS_MOCK";
+pub(crate) const ENV_REGISTRY_USER_AGENT: &str = "PODUP_REGISTRY_USER_AGENT";
+pub(crate) const ENV_REGISTRY_EXTRA_HEADERS: &str = "PODUP_REGISTRY_EXTRA_HEADERS";
+const DEFAULT_REGISTRY_USER_AGENT: &str =
+    concat!("pod-upgrade-trigger/", env!("CARGO_PKG_VERSION"));
+/// Headers the client manages itself; overriding them would break auth or framing.
+const RESERVED_REGISTRY_HEADERS: &[&str] = &[
+    "authorization",
+    "host",
+    "content-length",
+    "transfer-encoding",
+    "connection",
+];
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(crate) enum RegistryDigestStatus {
@@ -51,10 +68,55 @@ pub(crate) struct RegistryDigestRecord {
     pub from_cache: bool,
 }
 
+impl RegistryDigestRecord {
+    pub(crate) fn error_category(&self) -> Option<RegistryErrorCategory> {
+        self.error.as_deref().map(RegistryErrorCategory::from_code)
+    }
+}
+
+/// Coarse classification of registry failures, so callers can tell "try
+/// again later" apart from "this will never work without intervention".
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum RegistryErrorCategory {
+    NotFound,
+    Unauthorized,
+    RateLimited,
+    Network,
+    Parse,
+}
+
+impl RegistryErrorCategory {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            RegistryErrorCategory::NotFound => "not-found",
+            RegistryErrorCategory::Unauthorized => "unauthorized",
+            RegistryErrorCategory::RateLimited => "rate-limited",
+            RegistryErrorCategory::Network => "network",
+            RegistryErrorCategory::Parse => "parse",
+        }
+    }
+
+    pub(crate) fn is_retriable(self) -> bool {
+        matches!(
+            self,
+            RegistryErrorCategory::RateLimited | RegistryErrorCategory::Network
+        )
+    }
+
+    /// Categorizes an error code as persisted in the digest cache tables.
+    pub(crate) fn from_code(code: &str) -> Self {
+        RegistryDigestError::from_code(code).category()
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) enum RegistryDigestError {
     InvalidImage,
     Timeout,
+    Network,
+    ServerError,
+    NotFound,
+    RateLimited,
     Unauthorized,
     AuthMissing,
     AuthParse,
@@ -71,6 +133,10 @@ impl RegistryDigestError {
         match self {
             RegistryDigestError::InvalidImage => "invalid-image",
             RegistryDigestError::Timeout => "timeout",
+            RegistryDigestError::Network => "network-error",
+            RegistryDigestError::ServerError => "server-error",
+            RegistryDigestError::NotFound => "not-found",
+            RegistryDigestError::RateLimited => "rate-limited",
             RegistryDigestError::Unauthorized => "unauthorized",
             RegistryDigestError::AuthMissing => "auth-missing",
             RegistryDigestError::AuthParse => "auth-parse",
@@ -82,6 +148,50 @@ impl RegistryDigestError {
             RegistryDigestError::Json => "json-error",
         }
     }
+
+    /// Inverse of [`code`](Self::code); unknown codes map to `BadResponse`.
+    pub(crate) fn from_code(code: &str) -> Self {
+        match code.trim() {
+            "invalid-image" => RegistryDigestError::InvalidImage,
+            "timeout" => RegistryDigestError::Timeout,
+            "network-error" => RegistryDigestError::Network,
+            "server-error" => RegistryDigestError::ServerError,
+            "not-found" => RegistryDigestError::NotFound,
+            "rate-limited" => RegistryDigestError::RateLimited,
+            "unauthorized" => RegistryDigestError::Unauthorized,
+            "auth-missing" => RegistryDigestError::AuthMissing,
+            "auth-parse" => RegistryDigestError::AuthParse,
+            "challenge-parse" => RegistryDigestError::ChallengeParse,
+            "bad-response" => RegistryDigestError::BadResponse,
+            "digest-missing" => RegistryDigestError::DigestMissing,
+            "platform-not-found" => RegistryDigestError::PlatformNotFound,
+            "io-error" => RegistryDigestError::Io,
+            "json-error" => RegistryDigestError::Json,
+            _ => RegistryDigestError::BadResponse,
+        }
+    }
+
+    pub(crate) fn category(&self) -> RegistryErrorCategory {
+        match self {
+            RegistryDigestError::NotFound | RegistryDigestError::PlatformNotFound => {
+                RegistryErrorCategory::NotFound
+            }
+            RegistryDigestError::Unauthorized | RegistryDigestError::AuthMissing => {
+                RegistryErrorCategory::Unauthorized
+            }
+            RegistryDigestError::RateLimited => RegistryErrorCategory::RateLimited,
+            RegistryDigestError::Timeout
+            | RegistryDigestError::Network
+            | RegistryDigestError::ServerError
+            | RegistryDigestError::BadResponse
+            | RegistryDigestError::Io => RegistryErrorCategory::Network,
+            RegistryDigestError::InvalidImage
+            | RegistryDigestError::AuthParse
+            | RegistryDigestError::ChallengeParse
+            | RegistryDigestError::DigestMissing
+            | RegistryDigestError::Json => RegistryErrorCategory::Parse,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -168,24 +278,24 @@ pub(crate) async fn resolve_remote_manifest_digest(
         Err(_) => None,
     };
 
-    if let Some(row) = cached.as_ref() {
-        let expired = is_expired(row.checked_at, ttl_secs);
-        let stale = expired || row.status != RegistryDigestStatus::Ok;
-        if !force_refresh {
-            return RegistryDigestRecord {
-                image: row.image.clone(),
-                digest: row.digest.clone(),
-                checked_at: row.checked_at,
-                status: row.status,
-                error: row.error.clone(),
-                stale,
-                from_cache: true,
-            };
-        }
+    if !force_refresh && let Some(row) = cached.as_ref() {
+        return row.to_record(ttl_secs);
+    }
+
+    let flight = RefreshFlight::join(pool, &format!("digest:{}", parsed.normalized_image)).await;
+    if let Some(flight) = flight.as_ref().filter(|f| f.waited)
+        && let Ok(Some(row)) = get_cached_row(pool, &parsed.normalized_image).await
+        && row.checked_at >= flight.started_at
+    {
+        flight.release(pool).await;
+        return row.to_record(ttl_secs);
     }
 
     let previous_digest = cached.as_ref().and_then(|r| r.digest.clone());
-    match refresh_remote_manifest_digest(&parsed).await {
+    let started = Instant::now();
+    let refreshed = refresh_remote_manifest_digest(&parsed).await;
+    crate::record_request_timing(crate::TimingPhase::Registry, started.elapsed());
+    let record = match refreshed {
         Ok(digest) => {
             let record = upsert_cache_row(
                 pool,
@@ -232,7 +342,11 @@ pub(crate) async fn resolve_remote_manifest_digest(
                 from_cache: false,
             }
         }
+    };
+    if let Some(flight) = flight {
+        flight.release(pool).await;
     }
+    record
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -310,27 +424,31 @@ pub(crate) async fn resolve_remote_index_and_platform_digest(
         Err(_) => None,
     };
 
-    if let Some(row) = cached.as_ref() {
-        let stale = compute_stale(row.checked_at, ttl_secs, row.status);
-        if !force_refresh {
-            return RegistryPlatformDigestRecord {
-                image: row.image.clone(),
-                platform_os: row.platform_os.clone(),
-                platform_arch: row.platform_arch.clone(),
-                platform_variant: if row.platform_variant.is_empty() {
-                    None
-                } else {
-                    Some(row.platform_variant.clone())
-                },
-                remote_index_digest: row.remote_index_digest.clone(),
-                remote_platform_digest: row.remote_platform_digest.clone(),
-                checked_at: row.checked_at,
-                status: row.status,
-                error: row.error.clone(),
-                stale,
-                from_cache: true,
-            };
-        }
+    if !force_refresh && let Some(row) = cached.as_ref() {
+        return row.to_record(ttl_secs);
+    }
+
+    let flight = RefreshFlight::join(
+        pool,
+        &format!(
+            "digest:{}#{platform_os}/{platform_arch}/{platform_variant_key}",
+            parsed.normalized_image
+        ),
+    )
+    .await;
+    if let Some(flight) = flight.as_ref().filter(|f| f.waited)
+        && let Ok(Some(row)) = get_cached_platform_row(
+            pool,
+            &parsed.normalized_image,
+            platform_os,
+            platform_arch,
+            platform_variant_key,
+        )
+        .await
+        && row.checked_at >= flight.started_at
+    {
+        flight.release(pool).await;
+        return row.to_record(ttl_secs);
     }
 
     let previous_index = cached.as_ref().and_then(|r| r.remote_index_digest.clone());
@@ -338,14 +456,16 @@ pub(crate) async fn resolve_remote_index_and_platform_digest(
         .as_ref()
         .and_then(|r| r.remote_platform_digest.clone());
 
-    match refresh_remote_index_and_platform_digest(
+    let started = Instant::now();
+    let refreshed = refresh_remote_index_and_platform_digest(
         &parsed,
         platform_os,
         platform_arch,
         platform_variant_key,
     )
-    .await
-    {
+    .await;
+    crate::record_request_timing(crate::TimingPhase::Registry, started.elapsed());
+    let record = match refreshed {
         Ok((remote_index_digest, remote_platform_digest)) => {
             let record = upsert_platform_cache_row(
                 pool,
@@ -417,7 +537,11 @@ pub(crate) async fn resolve_remote_index_and_platform_digest(
                 from_cache: false,
             }
         }
+    };
+    if let Some(flight) = flight {
+        flight.release(pool).await;
     }
+    record
 }
 
 async fn refresh_remote_manifest_digest(
@@ -445,19 +569,7 @@ async fn refresh_remote_manifest_digest(
                         }
                         if let Some(err_obj) = entry.as_object() {
                             if let Some(code) = err_obj.get("error").and_then(|v| v.as_str()) {
-                                return Err(match code.trim() {
-                                    "timeout" => RegistryDigestError::Timeout,
-                                    "unauthorized" => RegistryDigestError::Unauthorized,
-                                    "auth-missing" => RegistryDigestError::AuthMissing,
-                                    "auth-parse" => RegistryDigestError::AuthParse,
-                                    "challenge-parse" => RegistryDigestError::ChallengeParse,
-                                    "bad-response" => RegistryDigestError::BadResponse,
-                                    "digest-missing" => RegistryDigestError::DigestMissing,
-                                    "platform-not-found" => RegistryDigestError::PlatformNotFound,
-                                    "io-error" => RegistryDigestError::Io,
-                                    "json-error" => RegistryDigestError::Json,
-                                    _ => RegistryDigestError::BadResponse,
-                                });
+                                return Err(RegistryDigestError::from_code(code));
                             }
                         }
                     }
@@ -705,19 +817,7 @@ async fn refresh_remote_index_and_platform_digest(
                         }
                         if let Some(err_obj) = entry.as_object() {
                             if let Some(code) = err_obj.get("error").and_then(|v| v.as_str()) {
-                                return Err(match code.trim() {
-                                    "timeout" => RegistryDigestError::Timeout,
-                                    "unauthorized" => RegistryDigestError::Unauthorized,
-                                    "auth-missing" => RegistryDigestError::AuthMissing,
-                                    "auth-parse" => RegistryDigestError::AuthParse,
-                                    "challenge-parse" => RegistryDigestError::ChallengeParse,
-                                    "bad-response" => RegistryDigestError::BadResponse,
-                                    "digest-missing" => RegistryDigestError::DigestMissing,
-                                    "platform-not-found" => RegistryDigestError::PlatformNotFound,
-                                    "io-error" => RegistryDigestError::Io,
-                                    "json-error" => RegistryDigestError::Json,
-                                    _ => RegistryDigestError::BadResponse,
-                                });
+                                return Err(RegistryDigestError::from_code(code));
                             }
 
                             let index = err_obj
@@ -782,10 +882,117 @@ async fn refresh_remote_index_and_platform_digest(
     Ok((remote_index_digest, remote_platform_digest))
 }
 
+/// Annotations and labels published with an image: OCI `annotations` from
+/// the index (if any) and the platform manifest, then `config.Labels` from the
+/// config blob. Later sources win when keys clash. Never cached.
+pub(crate) async fn fetch_remote_image_labels(
+    image: &str,
+    platform_os: &str,
+    platform_arch: &str,
+    platform_variant: Option<&str>,
+) -> Result<HashMap<String, String>, RegistryDigestError> {
+    let image = parse_image_ref(image)?;
+
+    if let Some(mocked) = mocked_image_labels(&image) {
+        return mocked;
+    }
+
+    let client = registry_http_client().map_err(|_| RegistryDigestError::BadResponse)?;
+    let mut labels = HashMap::new();
+
+    let manifest_url = format!(
+        "{}://{}/v2/{}/manifests/{}",
+        image.scheme, image.registry, image.repo, image.tag
+    );
+    let mut manifest = fetch_registry_json(&client, &image, &manifest_url).await?;
+    if manifest
+        .get("manifests")
+        .and_then(|v| v.as_array())
+        .is_some()
+    {
+        merge_string_map(manifest.get("annotations"), &mut labels);
+        let platform_digest = select_platform_digest_from_manifest_list(
+            &manifest,
+            platform_os,
+            platform_arch,
+            platform_variant.unwrap_or("").trim(),
+        )?
+        .ok_or(RegistryDigestError::PlatformNotFound)?;
+        let platform_url = format!(
+            "{}://{}/v2/{}/manifests/{}",
+            image.scheme, image.registry, image.repo, platform_digest
+        );
+        manifest = fetch_registry_json(&client, &image, &platform_url).await?;
+    }
+    merge_string_map(manifest.get("annotations"), &mut labels);
+
+    let config_digest = manifest
+        .pointer("/config/digest")
+        .and_then(|v| v.as_str())
+        .map(|s| s.trim())
+        .filter(|s| s.starts_with("sha256:"))
+        .ok_or(RegistryDigestError::DigestMissing)?;
+    let blob_url = format!(
+        "{}://{}/v2/{}/blobs/{}",
+        image.scheme, image.registry, image.repo, config_digest
+    );
+    let config = fetch_registry_json(&client, &image, &blob_url).await?;
+    merge_string_map(config.pointer("/config/Labels"), &mut labels);
+
+    Ok(labels)
+}
+
+/// Test-only label source keyed by normalized image: an object of labels, or
+/// a string error code.
+fn mocked_image_labels(
+    image: &ParsedImageRef,
+) -> Option<Result<HashMap<String, String>, RegistryDigestError>> {
+    let is_test = env::var("PODUP_ENV")
+        .ok()
+        .map(|v| v.to_ascii_lowercase())
+        .is_some_and(|v| v == "test" || v == "testing");
+    if !is_test {
+        return None;
+    }
+
+    let raw = env::var(ENV_REGISTRY_LABELS_MOCK).ok()?;
+    let value: Value = serde_json::from_str(&raw).ok()?;
+    let entry = value.get(&image.normalized_image)?;
+    if let Some(code) = entry.as_str() {
+        return Some(Err(RegistryDigestError::from_code(code)));
+    }
+    let mut labels = HashMap::new();
+    merge_string_map(Some(entry), &mut labels);
+    Some(Ok(labels))
+}
+
+async fn fetch_registry_json(
+    client: &Client,
+    image: &ParsedImageRef,
+    url: &str,
+) -> Result<Value, RegistryDigestError> {
+    let response = manifest_request_with_auth(client, image, reqwest::Method::GET, url).await?;
+    response.json().await.map_err(|_| RegistryDigestError::Json)
+}
+
+fn merge_string_map(source: Option<&Value>, into: &mut HashMap<String, String>) {
+    let Some(map) = source.and_then(|v| v.as_object()) else {
+        return;
+    };
+    for (key, value) in map {
+        if let Some(value) = value.as_str() {
+            into.insert(key.clone(), value.to_string());
+        }
+    }
+}
+
 fn map_reqwest_error(err: reqwest::Error) -> RegistryDigestError {
     if err.is_timeout() {
         return RegistryDigestError::Timeout;
     }
+    if err.is_connect() || err.is_request() {
+        return RegistryDigestError::Network;
+    }
     RegistryDigestError::BadResponse
 }
 
@@ -793,14 +1000,76 @@ fn map_status_to_error(status: StatusCode) -> RegistryDigestError {
     if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
         return RegistryDigestError::Unauthorized;
     }
+    if status == StatusCode::NOT_FOUND {
+        return RegistryDigestError::NotFound;
+    }
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return RegistryDigestError::RateLimited;
+    }
+    if status.is_server_error() {
+        return RegistryDigestError::ServerError;
+    }
     RegistryDigestError::BadResponse
 }
 
-fn registry_http_client() -> Result<Client, reqwest::Error> {
-    Client::builder()
+fn registry_http_client() -> Result<Client, String> {
+    crate::apply_tls_options(Client::builder())?
         .timeout(Duration::from_secs(3))
         .pool_max_idle_per_host(0)
+        .default_headers(registry_default_headers()?)
         .build()
+        .map_err(|err| err.to_string())
+}
+
+/// `User-Agent` plus `PODUP_REGISTRY_EXTRA_HEADERS` for every registry request.
+pub(crate) fn registry_default_headers() -> Result<HeaderMap, String> {
+    let ua = env::var(ENV_REGISTRY_USER_AGENT)
+        .ok()
+        .map(|raw| raw.trim().to_string())
+        .filter(|raw| !raw.is_empty())
+        .unwrap_or_else(|| DEFAULT_REGISTRY_USER_AGENT.to_string());
+    let ua = HeaderValue::from_str(&ua)
+        .map_err(|_| format!("{ENV_REGISTRY_USER_AGENT} contains invalid header characters"))?;
+
+    let mut headers = match env::var(ENV_REGISTRY_EXTRA_HEADERS) {
+        Ok(raw) => parse_registry_extra_headers(&raw)?,
+        Err(_) => HeaderMap::new(),
+    };
+    headers.insert(USER_AGENT, ua);
+    Ok(headers)
+}
+
+/// Parses `Name: Value` pairs separated by `;` or newlines. Names must be
+/// valid tokens and values must not contain control characters, so a value
+/// can never smuggle in extra header lines.
+pub(crate) fn parse_registry_extra_headers(raw: &str) -> Result<HeaderMap, String> {
+    let mut headers = HeaderMap::new();
+    for entry in raw.split([';', '\n']) {
+        let entry = entry.trim();
+        if entry.is_empty() {
+            continue;
+        }
+        let Some((name, value)) = entry.split_once(':') else {
+            return Err(format!("expected `Name: Value`, got {entry:?}"));
+        };
+        let name = HeaderName::from_bytes(name.trim().as_bytes())
+            .map_err(|_| format!("invalid header name {:?}", name.trim()))?;
+        if RESERVED_REGISTRY_HEADERS.contains(&name.as_str()) || name == USER_AGENT {
+            return Err(format!(
+                "header {name} cannot be overridden (use {ENV_REGISTRY_USER_AGENT} for the user agent)"
+            ));
+        }
+        let value = value.trim();
+        if value.chars().any(|c| c.is_control()) {
+            return Err(format!("header {name} value contains control characters"));
+        }
+        // Don't echo the value back: it may carry a proxy credential.
+        let mut value = HeaderValue::from_str(value)
+            .map_err(|_| format!("header {name} value contains invalid characters"))?;
+        value.set_sensitive(true);
+        headers.append(name, value);
+    }
+    Ok(headers)
 }
 
 fn manifest_accept_headers() -> HeaderMap {
@@ -1104,6 +1373,20 @@ struct CacheRow {
     error: Option<String>,
 }
 
+impl CacheRow {
+    fn to_record(&self, ttl_secs: u64) -> RegistryDigestRecord {
+        RegistryDigestRecord {
+            image: self.image.clone(),
+            digest: self.digest.clone(),
+            checked_at: self.checked_at,
+            status: self.status,
+            error: self.error.clone(),
+            stale: compute_stale(self.checked_at, ttl_secs, self.status),
+            from_cache: true,
+        }
+    }
+}
+
 async fn get_cached_row(pool: &SqlitePool, image: &str) -> Result<Option<CacheRow>, sqlx::Error> {
     let row = sqlx::query(
         "SELECT image, digest, checked_at, status, error FROM registry_digest_cache WHERE image = ?",
@@ -1128,6 +1411,28 @@ async fn get_cached_row(pool: &SqlitePool, image: &str) -> Result<Option<CacheRo
     }))
 }
 
+impl PlatformCacheRow {
+    fn to_record(&self, ttl_secs: u64) -> RegistryPlatformDigestRecord {
+        RegistryPlatformDigestRecord {
+            image: self.image.clone(),
+            platform_os: self.platform_os.clone(),
+            platform_arch: self.platform_arch.clone(),
+            platform_variant: if self.platform_variant.is_empty() {
+                None
+            } else {
+                Some(self.platform_variant.clone())
+            },
+            remote_index_digest: self.remote_index_digest.clone(),
+            remote_platform_digest: self.remote_platform_digest.clone(),
+            checked_at: self.checked_at,
+            status: self.status,
+            error: self.error.clone(),
+            stale: compute_stale(self.checked_at, ttl_secs, self.status),
+            from_cache: true,
+        }
+    }
+}
+
 async fn get_cached_platform_row(
     pool: &SqlitePool,
     image: &str,
@@ -1264,6 +1569,67 @@ async fn upsert_platform_cache_row(
     })
 }
 
+/// Single-flight for registry refreshes across processes (each HTTP request
+/// and the scheduler run separately), coordinated through a short-lived
+/// `image_locks` row. A caller that had to wait while another refresh ran
+/// reuses the cache row it wrote instead of asking the registry again.
+struct RefreshFlight {
+    bucket: String,
+    started_at: i64,
+    waited: bool,
+}
+
+impl RefreshFlight {
+    /// Waits for the refresh slot of `bucket`. A row older than
+    /// `REFRESH_FLIGHT_STALE_SECS` belongs to a refresh that died and is taken
+    /// over. `None` when the database is unusable; callers then refresh
+    /// uncoordinated.
+    async fn join(pool: &SqlitePool, bucket: &str) -> Option<Self> {
+        let started_at = crate::current_unix_secs() as i64;
+        let mut waited = false;
+        loop {
+            let now = crate::current_unix_secs() as i64;
+            let inserted = sqlx::query(
+                "INSERT INTO image_locks (bucket, acquired_at) VALUES (?, ?) ON CONFLICT DO NOTHING",
+            )
+            .bind(bucket)
+            .bind(now)
+            .execute(pool)
+            .await
+            .ok()?
+            .rows_affected();
+            let taken_over = inserted == 0
+                && sqlx::query(
+                    "UPDATE image_locks SET acquired_at = ? WHERE bucket = ? AND acquired_at < ?",
+                )
+                .bind(now)
+                .bind(bucket)
+                .bind(now - REFRESH_FLIGHT_STALE_SECS)
+                .execute(pool)
+                .await
+                .ok()?
+                .rows_affected()
+                    > 0;
+            if inserted > 0 || taken_over {
+                return Some(Self {
+                    bucket: bucket.to_string(),
+                    started_at,
+                    waited,
+                });
+            }
+            waited = true;
+            tokio::time::sleep(REFRESH_FLIGHT_POLL).await;
+        }
+    }
+
+    async fn release(&self, pool: &SqlitePool) {
+        let _ = sqlx::query("DELETE FROM image_locks WHERE bucket = ?")
+            .bind(&self.bucket)
+            .execute(pool)
+            .await;
+    }
+}
+
 fn is_expired(checked_at: i64, ttl_secs: u64) -> bool {
     let now = crate::current_unix_secs() as i64;
     let age = now.saturating_sub(checked_at).max(0) as u64;
@@ -1277,6 +1643,7 @@ fn compute_stale(checked_at: i64, ttl_secs: u64, status: RegistryDigestStatus) -
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
     use sqlx::sqlite::SqlitePoolOptions;
     use std::io::{Read, Write};
     use std::net::{TcpListener, TcpStream};
@@ -1284,10 +1651,26 @@ mod tests {
     use std::sync::{Mutex, OnceLock};
     use tempfile::TempDir;
 
-    static ENV_MUTEX: OnceLock<Mutex<()>> = OnceLock::new();
+    // An async mutex: every test taking it awaits while holding it.
+    static ENV_MUTEX: OnceLock<tokio::sync::Mutex<()>> = OnceLock::new();
+
+    async fn env_lock() -> tokio::sync::MutexGuard<'static, ()> {
+        ENV_MUTEX
+            .get_or_init(|| tokio::sync::Mutex::new(()))
+            .lock()
+            .await
+    }
 
-    fn env_lock() -> std::sync::MutexGuard<'static, ()> {
-        ENV_MUTEX.get_or_init(|| Mutex::new(())).lock().unwrap()
+    #[test]
+    fn parse_image_ref_keeps_registry_port() {
+        let parsed = parse_image_ref("registry.internal:5000/owner/name:tag").unwrap();
+        assert_eq!(parsed.registry, "registry.internal:5000");
+        assert_eq!(parsed.repo, "owner/name");
+        assert_eq!(parsed.tag, "tag");
+        assert_eq!(
+            parsed.normalized_image,
+            "registry.internal:5000/owner/name:tag"
+        );
     }
 
     #[test]
@@ -1557,7 +1940,7 @@ mod tests {
 
     #[tokio::test(flavor = "current_thread")]
     async fn auth_json_username_password_and_scheme_key_supported() {
-        let _lock = env_lock();
+        let _lock = env_lock().await;
         let temp = TempDir::new().unwrap();
         let _home = HomeGuard::set(temp.path());
 
@@ -1585,7 +1968,7 @@ mod tests {
 
     #[tokio::test(flavor = "current_thread")]
     async fn remote_digest_200_header_ok() {
-        let _lock = env_lock();
+        let _lock = env_lock().await;
         let temp = TempDir::new().unwrap();
         let _home = HomeGuard::set(temp.path());
         let pool = test_pool().await;
@@ -1611,7 +1994,7 @@ mod tests {
 
     #[tokio::test(flavor = "current_thread")]
     async fn remote_digest_401_bearer_challenge_then_ok() {
-        let _lock = env_lock();
+        let _lock = env_lock().await;
         let temp = TempDir::new().unwrap();
         let _home = HomeGuard::set(temp.path());
         let pool = test_pool().await;
@@ -1668,7 +2051,7 @@ mod tests {
 
     #[tokio::test(flavor = "current_thread")]
     async fn remote_digest_missing_auth_returns_auth_missing() {
-        let _lock = env_lock();
+        let _lock = env_lock().await;
         let temp = TempDir::new().unwrap();
         let _home = HomeGuard::set(temp.path());
         let pool = test_pool().await;
@@ -1697,7 +2080,7 @@ mod tests {
 
     #[tokio::test(flavor = "current_thread")]
     async fn remote_digest_200_without_digest_header_returns_digest_missing() {
-        let _lock = env_lock();
+        let _lock = env_lock().await;
         let temp = TempDir::new().unwrap();
         let _home = HomeGuard::set(temp.path());
         let pool = test_pool().await;
@@ -1719,9 +2102,175 @@ mod tests {
         assert_eq!(record.error.as_deref(), Some("digest-missing"));
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    async fn remote_digest_error_status_maps_to_category() {
+        let _lock = env_lock().await;
+        let temp = TempDir::new().unwrap();
+        let _home = HomeGuard::set(temp.path());
+        let pool = test_pool().await;
+
+        for (status, code, category, retriable) in [
+            (404, "not-found", RegistryErrorCategory::NotFound, false),
+            (
+                429,
+                "rate-limited",
+                RegistryErrorCategory::RateLimited,
+                true,
+            ),
+            (503, "server-error", RegistryErrorCategory::Network, true),
+        ] {
+            let server = MockServer::start(move |_addr| {
+                vec![Step {
+                    method: "HEAD",
+                    path_prefix: "/v2/repo/manifests/tag",
+                    expect_auth: AuthExpectation::None,
+                    status,
+                    headers: vec![],
+                    body: None,
+                }]
+            });
+
+            let image = format!("http://{}/repo:tag", server.addr);
+            let record = resolve_remote_manifest_digest(&pool, &image, 600, true).await;
+            assert_eq!(record.status, RegistryDigestStatus::Error);
+            assert_eq!(record.error.as_deref(), Some(code));
+            assert_eq!(record.error_category(), Some(category));
+            assert_eq!(category.is_retriable(), retriable);
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn remote_image_labels_merge_annotations_and_config_labels() {
+        let _lock = env_lock().await;
+        let temp = TempDir::new().unwrap();
+        let _home = HomeGuard::set(temp.path());
+
+        let index = json!({
+            "schemaVersion": 2,
+            "annotations": { "io.podup.skip": "false", "index.only": "1" },
+            "manifests": [
+                { "digest": "sha256:arm", "platform": { "os": "linux", "architecture": "arm64" } },
+                { "digest": "sha256:amd", "platform": { "os": "linux", "architecture": "amd64" } }
+            ]
+        });
+        let manifest = json!({
+            "schemaVersion": 2,
+            "config": { "digest": "sha256:cfg" },
+            "annotations": { "io.podup.skip": "true" }
+        });
+        let config = json!({ "config": { "Labels": { "org.example.team": "infra" } } });
+        let json_step = |path_prefix: &'static str, body: &Value| Step {
+            method: "GET",
+            path_prefix,
+            expect_auth: AuthExpectation::None,
+            status: 200,
+            headers: vec![("Content-Type", "application/json".to_string())],
+            body: Some(body.to_string()),
+        };
+        let server = MockServer::start(|_addr| {
+            vec![
+                json_step("/v2/repo/manifests/tag", &index),
+                json_step("/v2/repo/manifests/sha256:amd", &manifest),
+                json_step("/v2/repo/blobs/sha256:cfg", &config),
+            ]
+        });
+
+        let image = format!("http://{}/repo:tag", server.addr);
+        let labels = fetch_remote_image_labels(&image, "linux", "amd64", None)
+            .await
+            .unwrap();
+        assert_eq!(
+            labels.get("io.podup.skip").map(String::as_str),
+            Some("true")
+        );
+        assert_eq!(labels.get("index.only").map(String::as_str), Some("1"));
+        assert_eq!(
+            labels.get("org.example.team").map(String::as_str),
+            Some("infra")
+        );
+        assert_eq!(server.hits(), 3);
+    }
+
+    #[test]
+    fn error_codes_round_trip_and_categorize() {
+        assert_eq!(
+            RegistryErrorCategory::from_code("auth-missing"),
+            RegistryErrorCategory::Unauthorized
+        );
+        assert_eq!(
+            RegistryErrorCategory::from_code("platform-not-found"),
+            RegistryErrorCategory::NotFound
+        );
+        assert_eq!(
+            RegistryErrorCategory::from_code("timeout"),
+            RegistryErrorCategory::Network
+        );
+        assert_eq!(
+            RegistryErrorCategory::from_code("digest-missing"),
+            RegistryErrorCategory::Parse
+        );
+        for code in ["not-found", "rate-limited", "network-error", "json-error"] {
+            assert_eq!(RegistryDigestError::from_code(code).code(), code);
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn concurrent_refreshes_share_one_registry_lookup() {
+        let _lock = env_lock().await;
+        let temp = TempDir::new().unwrap();
+        let _home = HomeGuard::set(temp.path());
+        let pool = test_pool().await;
+
+        let digest = "sha256:shared";
+        let step = Step {
+            method: "HEAD",
+            path_prefix: "/v2/repo/manifests/tag",
+            expect_auth: AuthExpectation::None,
+            status: 200,
+            headers: vec![("Docker-Content-Digest", digest.to_string())],
+            body: None,
+        };
+        let server = MockServer::start(|_addr| vec![step.clone(), step.clone(), step]);
+
+        let image = format!("http://{}/repo:tag", server.addr);
+        let (first, second) = tokio::join!(
+            resolve_remote_manifest_digest(&pool, &image, 600, true),
+            resolve_remote_manifest_digest(&pool, &image, 600, true),
+        );
+        assert_eq!(first.digest.as_deref(), Some(digest));
+        assert_eq!(second.digest.as_deref(), Some(digest));
+        assert_eq!(server.hits(), 1);
+        assert!(first.from_cache != second.from_cache);
+
+        // A later refresh still goes to the registry.
+        let third = resolve_remote_manifest_digest(&pool, &image, 600, true).await;
+        assert!(!third.from_cache);
+        assert_eq!(server.hits(), 2);
+        let flights: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM image_locks")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(flights, 0);
+
+        // A flight row left behind by a dead process is taken over.
+        let now = crate::current_unix_secs() as i64;
+        sqlx::query("INSERT INTO image_locks (bucket, acquired_at) VALUES (?, ?)")
+            .bind(format!(
+                "digest:{}",
+                parse_image_ref(&image).unwrap().normalized_image
+            ))
+            .bind(now - REFRESH_FLIGHT_STALE_SECS - 1)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let fourth = resolve_remote_manifest_digest(&pool, &image, 600, true).await;
+        assert!(!fourth.from_cache);
+        assert_eq!(server.hits(), 3);
+    }
+
     #[tokio::test(flavor = "current_thread")]
     async fn cache_ttl_hit_expired_force_refresh_and_failure_fallback() {
-        let _lock = env_lock();
+        let _lock = env_lock().await;
         let temp = TempDir::new().unwrap();
         let _home = HomeGuard::set(temp.path());
         let pool = test_pool().await;
@@ -1818,4 +2367,20 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn parse_registry_extra_headers_validates_pairs() {
+        let headers =
+            parse_registry_extra_headers("X-Proxy-Team: infra; X-Trace: a:b\nX-Trace: c").unwrap();
+        assert_eq!(headers.get("x-proxy-team").unwrap(), "infra");
+        let traces: Vec<_> = headers.get_all("x-trace").iter().collect();
+        assert_eq!(traces, ["a:b", "c"]);
+        assert!(parse_registry_extra_headers("").unwrap().is_empty());
+
+        assert!(parse_registry_extra_headers("NoColon").is_err());
+        assert!(parse_registry_extra_headers("Bad Name: v").is_err());
+        assert!(parse_registry_extra_headers("X-Evil: a\rInjected: b").is_err());
+        assert!(parse_registry_extra_headers("Authorization: Bearer x").is_err());
+        assert!(parse_registry_extra_headers("user-agent: custom").is_err());
+    }
 }
@@ -117,6 +117,24 @@ impl SystemdRunExecutor {
     }
 }
 
+/// Keeps the resource limits a transient unit was started with in the task
+/// meta and log, so they can be audited next to the task's outcome.
+fn record_unit_properties(task_id: &str, properties: &[String]) {
+    if properties.is_empty() {
+        return;
+    }
+    crate::store_task_unit_properties(task_id, properties);
+    crate::append_task_log(
+        task_id,
+        "info",
+        "systemd-run-properties",
+        "running",
+        &format!("Transient unit properties: {}", properties.join(" ")),
+        None,
+        json!({ "properties": properties }),
+    );
+}
+
 impl TaskExecutor for SystemdRunExecutor {
     fn kind(&self) -> &'static str {
         "systemd-run"
@@ -148,7 +166,10 @@ impl TaskExecutor for SystemdRunExecutor {
 
         match request {
             DispatchRequest::GithubWebhook { runner_unit } => {
-                let args = crate::build_systemd_run_args(runner_unit, exe_str, task_id);
+                let properties = crate::systemd_run_properties();
+                record_unit_properties(task_id, &properties);
+                let args =
+                    crate::build_systemd_run_args(runner_unit, exe_str, task_id, &properties);
                 match self.dispatch_systemd_run(args, true) {
                     Ok(()) => Ok(()),
                     Err(err) if err.code == "systemd-run-spawn-failed" => {
@@ -181,6 +202,11 @@ impl TaskExecutor for SystemdRunExecutor {
                 for env_kv in crate::collect_run_task_env() {
                     args.push(format!("--setenv={env_kv}"));
                 }
+                let properties = crate::systemd_run_properties();
+                record_unit_properties(task_id, &properties);
+                for property in &properties {
+                    args.push(format!("--property={property}"));
+                }
                 args.push(exe_str.to_string());
                 args.push("run-task".to_string());
                 args.push(task_id.to_string());
@@ -0,0 +1,135 @@
+//! Minimal server side of RFC 6455: the opening handshake plus unfragmented
+//! frame encoding/decoding, enough to push task logs to browsers whose
+//! proxies buffer SSE.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use sha1::{Digest, Sha1};
+use std::io::{self, Read};
+
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Largest client frame we accept. Clients only send control frames here.
+const MAX_CLIENT_PAYLOAD: u64 = 64 * 1024;
+
+pub(crate) const OPCODE_TEXT: u8 = 0x1;
+pub(crate) const OPCODE_CLOSE: u8 = 0x8;
+pub(crate) const OPCODE_PING: u8 = 0x9;
+pub(crate) const OPCODE_PONG: u8 = 0xA;
+
+pub(crate) const CLOSE_NORMAL: u16 = 1000;
+
+/// `Sec-WebSocket-Accept` value for a client's `Sec-WebSocket-Key`.
+pub(crate) fn accept_key(client_key: &str) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(client_key.trim().as_bytes());
+    hasher.update(HANDSHAKE_GUID.as_bytes());
+    BASE64_STANDARD.encode(hasher.finalize())
+}
+
+/// Encodes a single unmasked (server-to-client) frame with FIN set.
+pub(crate) fn encode_frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    frame.push(0x80 | (opcode & 0x0F));
+    let len = payload.len();
+    if len < 126 {
+        frame.push(len as u8);
+    } else if len <= u16::MAX as usize {
+        frame.push(126);
+        frame.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        frame.push(127);
+        frame.extend_from_slice(&(len as u64).to_be_bytes());
+    }
+    frame.extend_from_slice(payload);
+    frame
+}
+
+pub(crate) fn encode_close(code: u16, reason: &str) -> Vec<u8> {
+    let mut payload = code.to_be_bytes().to_vec();
+    payload.extend_from_slice(reason.as_bytes());
+    encode_frame(OPCODE_CLOSE, &payload)
+}
+
+pub(crate) struct Frame {
+    pub(crate) opcode: u8,
+    pub(crate) payload: Vec<u8>,
+}
+
+/// Reads one client frame, unmasking its payload. `Ok(None)` on a clean EOF
+/// before the frame started.
+pub(crate) fn read_frame(reader: &mut impl Read) -> io::Result<Option<Frame>> {
+    let mut header = [0_u8; 2];
+    match reader.read_exact(&mut header) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err),
+    }
+
+    let opcode = header[0] & 0x0F;
+    let masked = header[1] & 0x80 != 0;
+    let mut len = u64::from(header[1] & 0x7F);
+    if len == 126 {
+        let mut ext = [0_u8; 2];
+        reader.read_exact(&mut ext)?;
+        len = u64::from(u16::from_be_bytes(ext));
+    } else if len == 127 {
+        let mut ext = [0_u8; 8];
+        reader.read_exact(&mut ext)?;
+        len = u64::from_be_bytes(ext);
+    }
+    if len > MAX_CLIENT_PAYLOAD {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("websocket frame too large: {len} bytes"),
+        ));
+    }
+
+    let mut mask = [0_u8; 4];
+    if masked {
+        reader.read_exact(&mut mask)?;
+    }
+    let mut payload = vec![0_u8; len as usize];
+    reader.read_exact(&mut payload)?;
+    if masked {
+        for (idx, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[idx % 4];
+        }
+    }
+
+    Ok(Some(Frame { opcode, payload }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accept_key_matches_rfc_example() {
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn frames_round_trip_through_client_masking() {
+        let short = encode_frame(OPCODE_TEXT, b"hi");
+        assert_eq!(short, vec![0x81, 2, b'h', b'i']);
+
+        let long = encode_frame(OPCODE_TEXT, &[b'x'; 300]);
+        assert_eq!(&long[..4], &[0x81, 126, 0x01, 0x2C]);
+
+        // A masked client close frame as a browser would send it.
+        let mask = [1_u8, 2, 3, 4];
+        let body = [0x03_u8, 0xE8];
+        let mut raw = vec![0x88, 0x80 | body.len() as u8];
+        raw.extend_from_slice(&mask);
+        raw.extend(body.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        let frame = read_frame(&mut raw.as_slice()).unwrap().unwrap();
+        assert_eq!(frame.opcode, OPCODE_CLOSE);
+        assert_eq!(frame.payload, body);
+
+        assert!(read_frame(&mut [].as_slice()).unwrap().is_none());
+    }
+}
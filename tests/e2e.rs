@@ -1,3 +1,4 @@
+use flate2::read::GzDecoder;
 use hmac::{Hmac, Mac};
 use serde_json::{Value, json};
 use sha2::Sha256;
@@ -10,7 +11,7 @@ use std::net::TcpStream;
 use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tempfile::TempDir;
 
 type AnyResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
@@ -26,42 +27,102 @@ async fn e2e_full_suite() -> AnyResult<()> {
     }
 
     run_scenario!(scenario_auto_discovery);
+    run_scenario!(scenario_validate_config);
+    run_scenario!(scenario_import_self_update_reports_cli);
+    run_scenario!(scenario_manual_trigger_pattern);
     run_scenario!(scenario_auto_discovery_podman_ps_skips_missing_unit_label);
+    run_scenario!(scenario_units_status);
+    run_scenario!(scenario_db_backup);
+    run_scenario!(scenario_read_only_mode);
     run_scenario!(scenario_webhook_auto_discovery_toggle);
     run_scenario!(scenario_health_db_error);
+    run_scenario!(scenario_health_disk_space);
+    run_scenario!(scenario_health_systemd);
     run_scenario!(scenario_github_webhook);
+    run_scenario!(scenario_github_webhook_form_encoded);
+    run_scenario!(scenario_task_executor_by_kind);
+    run_scenario!(scenario_generic_webhook_signature);
+    run_scenario!(scenario_github_image_policy);
+    run_scenario!(scenario_github_tag_filter);
+    run_scenario!(scenario_webhook_replay);
+    run_scenario!(scenario_manual_service_simulate_webhook);
+    run_scenario!(scenario_github_webhook_preview);
     run_scenario!(scenario_webhook_image_prune_success);
     run_scenario!(scenario_webhook_image_prune_failure);
     run_scenario!(scenario_github_dispatch_failure);
     run_scenario!(scenario_rate_limit_and_prune);
     run_scenario!(scenario_task_prune_retention);
+    run_scenario!(scenario_task_log_max_entries);
+    run_scenario!(scenario_tasks_stats);
+    run_scenario!(scenario_tasks_active);
+    run_scenario!(scenario_unit_history);
+    run_scenario!(scenario_task_concurrency_queue);
     run_scenario!(scenario_settings_tasks_retention);
     run_scenario!(scenario_manual_api);
     run_scenario!(scenario_manual_service_image_verify_multi_arch);
+    run_scenario!(scenario_pull_digest_mismatch);
+    run_scenario!(scenario_github_webhook_skip_annotation);
+    run_scenario!(scenario_github_webhook_unit_lock);
+    run_scenario!(scenario_image_locks_filter_and_release);
+    run_scenario!(scenario_systemd_run_properties);
+    run_scenario!(scenario_github_webhook_default_tag);
+    run_scenario!(scenario_units_discovered_verbose);
+    run_scenario!(scenario_discovery_degrades_without_container_dir);
+    run_scenario!(scenario_db_migrations_status);
+    run_scenario!(scenario_manual_restart_action);
+    run_scenario!(scenario_prod_requires_forward_auth);
+    run_scenario!(scenario_manual_deploy_image);
+    run_scenario!(scenario_scheduler_loadavg_deferral);
+    run_scenario!(scenario_api_error_envelope);
+    run_scenario!(scenario_tasks_stop_all_cancels_queued);
+    run_scenario!(scenario_scheduler_unit_max_stale);
+    run_scenario!(scenario_secret_file_env);
+    run_scenario!(scenario_token_hook);
+    run_scenario!(scenario_scheduler_pause);
+    run_scenario!(scenario_manual_service_plan);
+    run_scenario!(scenario_task_tags);
+    run_scenario!(scenario_podman_health_ttl);
+    run_scenario!(scenario_events_sse);
+    run_scenario!(scenario_task_create_validation);
+    run_scenario!(scenario_units_discover);
+    run_scenario!(scenario_trigger_units_stdin);
+    run_scenario!(scenario_github_event_prefilter);
     run_scenario!(scenario_manual_service_upgrade_requires_digest_switch);
     run_scenario!(scenario_manual_service_upgrade_marks_anomaly_when_digest_unchanged);
     run_scenario!(scenario_manual_service_upgrade_clone_fallback_create_command);
     run_scenario!(scenario_csrf_guard);
     run_scenario!(scenario_self_update_api);
+    run_scenario!(scenario_version_check_cache);
     run_scenario!(scenario_forwardauth_and_csrf_strict_mode);
     run_scenario!(scenario_manual_services_update_tag_update_available);
     run_scenario!(scenario_manual_services_update_latest_ahead);
     run_scenario!(scenario_manual_services_update_up_to_date_tag_latest);
     run_scenario!(scenario_manual_services_update_up_to_date_tag_latest_podman_systemd_unit_label);
     run_scenario!(scenario_manual_services_update_unknown_container_not_found);
+    run_scenario!(scenario_manual_service_image_override);
     run_scenario!(scenario_manual_auto_update_failure);
     run_scenario!(scenario_manual_task_command_meta_and_unit_errors);
     run_scenario!(scenario_manual_task_unit_failure_diagnostics);
     run_scenario!(scenario_manual_dispatch_failure);
     run_scenario!(scenario_scheduler_loop);
+    run_scenario!(scenario_scheduler_dry_run);
+    run_scenario!(scenario_scheduler_digest_gate);
     run_scenario!(scenario_scheduler_dispatch_failure);
     run_scenario!(scenario_events_task_filter);
+    run_scenario!(scenario_events_csv_export);
     run_scenario!(scenario_task_command_logs);
+    run_scenario!(scenario_task_logs_cli);
     run_scenario!(scenario_task_logs_sse);
+    run_scenario!(scenario_task_logs_ws);
+    run_scenario!(scenario_sse_token_auth);
     run_scenario!(scenario_error_paths);
     run_scenario!(scenario_static_assets);
+    run_scenario!(scenario_base_path);
+    run_scenario!(scenario_method_not_allowed);
+    run_scenario!(scenario_gzip_responses);
     run_scenario!(scenario_cli_maintenance);
     run_scenario!(scenario_http_server);
+    run_scenario!(scenario_request_timeout);
     Ok(())
 }
 
@@ -87,7 +148,7 @@ async fn scenario_csrf_guard() -> AnyResult<()> {
         HttpRequest::post("/api/tasks")
             .header("content-type", "application/json")
             .header("x-podup-csrf", "1")
-            .body(b"{}".to_vec()),
+            .body(br#"{"units":["svc-alpha.service"]}"#.to_vec()),
     )?;
     assert_eq!(
         ok.status, 200,
@@ -96,6 +157,89 @@ async fn scenario_csrf_guard() -> AnyResult<()> {
     let body = ok.json_body()?;
     let task_id = body["task_id"].as_str().unwrap_or_default();
     assert!(!task_id.is_empty(), "tasks-create should return a task_id");
+    assert_eq!(body["is_long_running"], Value::from(true));
+
+    let create = |body: Value| {
+        HttpRequest::post("/api/tasks")
+            .header("content-type", "application/json")
+            .header("x-podup-csrf", "1")
+            .body(body.to_string().into_bytes())
+    };
+    let maintenance = env.send_request(create(json!({
+        "kind": "maintenance",
+        "timeout_secs": 90,
+    })))?;
+    assert_eq!(maintenance.status, 200, "{}", maintenance.body_text());
+    let body = maintenance.json_body()?;
+    assert_eq!(body["is_long_running"], Value::from(false));
+    assert_eq!(body["timeout_secs"], Value::from(90));
+    let detail = env
+        .send_request(HttpRequest::get(&format!(
+            "/api/tasks/{}",
+            body["task_id"].as_str().unwrap_or_default()
+        )))?
+        .json_body()?;
+    assert_eq!(detail["is_long_running"], Value::from(false));
+    assert_eq!(detail["logs"][0]["meta"]["timeout_secs"], Value::from(90));
+
+    let explicit = env.send_request(create(json!({
+        "kind": "maintenance",
+        "is_long_running": true,
+    })))?;
+    assert_eq!(explicit.json_body()?["is_long_running"], Value::from(true));
+
+    let zero = env.send_request(create(json!({
+        "units": ["svc-alpha.service"],
+        "timeout_secs": 0,
+    })))?;
+    assert_eq!(zero.status, 400, "{}", zero.body_text());
+
+    Ok(())
+}
+
+async fn scenario_version_check_cache() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let pool = env.connect_db().await?;
+    let fetched_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    sqlx::query(
+        "INSERT INTO version_check_cache (id, release_tag, published_at, fetched_at) \
+         VALUES (1, 'v99.0.0', NULL, ?)",
+    )
+    .bind(fetched_at)
+    .execute(&pool)
+    .await?;
+
+    // Route GitHub traffic to a closed port so any fetch fails fast.
+    let offline = |cmd: &mut Command| {
+        cmd.env("HTTPS_PROXY", "http://127.0.0.1:9");
+        cmd.env("https_proxy", "http://127.0.0.1:9");
+    };
+
+    let cached = env.send_request_with_env(HttpRequest::get("/api/version/check"), offline)?;
+    assert_eq!(cached.status, 200, "{}", cached.body_text());
+    let body = cached.json_body()?;
+    assert_eq!(body["latest"]["release_tag"], "v99.0.0");
+    assert_eq!(body["cached_at"], Value::from(fetched_at));
+    assert_eq!(body["stale"], Value::from(false));
+    assert_eq!(body["has_update"], Value::from(true));
+
+    // A forced refresh that cannot reach GitHub falls back to the cached value.
+    let refreshed =
+        env.send_request_with_env(HttpRequest::get("/api/version/check?refresh=1"), offline)?;
+    assert_eq!(refreshed.status, 200, "{}", refreshed.body_text());
+    let body = refreshed.json_body()?;
+    assert_eq!(body["latest"]["release_tag"], "v99.0.0");
+    assert_eq!(body["stale"], Value::from(true));
+    assert!(body["error"].is_string());
+
+    sqlx::query("DELETE FROM version_check_cache")
+        .execute(&pool)
+        .await?;
+    let failed = env.send_request_with_env(HttpRequest::get("/api/version/check"), offline)?;
+    assert_eq!(failed.status, 503, "{}", failed.body_text());
+    assert_eq!(failed.json_body()?["error"]["code"], "version-check-failed");
 
     Ok(())
 }
@@ -133,9 +277,16 @@ async fn scenario_self_update_api() -> AnyResult<()> {
     );
     let missing_body = missing_command.json_body()?;
     assert_eq!(
-        missing_body["error"],
+        missing_body["error"]["code"],
         Value::from("self-update-command-missing")
     );
+    assert_eq!(missing_body["error"]["details"]["reason"], "missing");
+    assert!(
+        !missing_command
+            .body_text()
+            .contains("PODUP_SELF_UPDATE_COMMAND"),
+        "env var names stay out of the response"
+    );
 
     let script = env.state_dir.join("fake-self-update.sh");
     fs::write(
@@ -189,6 +340,60 @@ echo "self-update-ok"
     }
     assert_eq!(status, "succeeded", "task should succeed");
 
+    let probe = env.state_dir.join("probe-self-update.sh");
+    fs::write(
+        &probe,
+        r#"#!/usr/bin/env bash
+if [ "${1:-}" != "--dry-run" ] || [ "${PODUP_SELF_UPDATE_DRY_RUN:-}" != "1" ]; then
+  echo "dry-run not forced" >&2
+  exit 3
+fi
+echo "would update to latest" >&2
+exit 0
+"#,
+    )?;
+    let mut perms = fs::metadata(&probe)?.permissions();
+    perms.set_mode(0o755);
+    fs::set_permissions(&probe, perms)?;
+
+    let tasks_before: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tasks")
+        .fetch_one(&pool)
+        .await?;
+    let test = env.send_request_with_env(
+        HttpRequest::post("/api/self-update/test").header("x-podup-csrf", "1"),
+        |cmd| {
+            cmd.env("PODUP_SELF_UPDATE_COMMAND", &probe);
+            cmd.env("PODUP_SELF_UPDATE_DRY_RUN", "0");
+        },
+    )?;
+    assert_eq!(test.status, 200, "{}", test.body_text());
+    let body = test.json_body()?;
+    assert_eq!(body["dry_run"], Value::from(true));
+    assert_eq!(body["success"], Value::from(true));
+    assert_eq!(body["exit_code"], Value::from(0));
+    assert_eq!(body["timed_out"], Value::from(false));
+    assert!(
+        body["stderr"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("would update to latest")
+    );
+    let tasks_after: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tasks")
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(
+        tasks_before, tasks_after,
+        "self-update test must not create tasks"
+    );
+
+    let missing = env.send_request_with_env(
+        HttpRequest::post("/api/self-update/test").header("x-podup-csrf", "1"),
+        |cmd| {
+            cmd.env_remove("PODUP_SELF_UPDATE_COMMAND");
+        },
+    )?;
+    assert_eq!(missing.status, 503);
+
     Ok(())
 }
 
@@ -207,6 +412,11 @@ async fn scenario_forwardauth_and_csrf_strict_mode() -> AnyResult<()> {
     let no_admin =
         env.send_request_with_env(HttpRequest::get("/api/manual/services"), configure_strict)?;
     assert_eq!(no_admin.status, 401);
+    assert_eq!(no_admin.json_body()?["error"]["code"], "unauthorized");
+    assert!(
+        !no_admin.body_text().contains("x-test-admin"),
+        "the ForwardAuth header name stays out of the response"
+    );
 
     // With admin header it should succeed (no CSRF for GET).
     let admin_ok = env.send_request_with_env(
@@ -244,6 +454,162 @@ async fn scenario_forwardauth_and_csrf_strict_mode() -> AnyResult<()> {
     Ok(())
 }
 
+async fn scenario_manual_trigger_pattern() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let trigger = |body: Value| -> AnyResult<HttpResponse> {
+        env.send_request(
+            HttpRequest::post("/api/manual/trigger")
+                .header("content-type", "application/json")
+                .header("x-podup-csrf", "1")
+                .body(body.to_string().into_bytes()),
+        )
+    };
+
+    let glob = trigger(json!({ "pattern": "svc-*", "dry_run": true }))?;
+    assert_eq!(glob.status, 202);
+    let mut units: Vec<String> = glob.json_body()?["triggered"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|r| r["unit"].as_str().map(str::to_string))
+        .collect();
+    units.sort();
+    assert_eq!(units, vec!["svc-alpha.service", "svc-beta.service"]);
+
+    let union = trigger(json!({
+        "pattern": "/^svc-a.+$/",
+        "units": ["podman-auto-update.service"],
+        "dry_run": true,
+    }))?;
+    assert_eq!(union.status, 202);
+    let triggered = union.json_body()?["triggered"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    assert_eq!(
+        triggered.len(),
+        2,
+        "explicit units are unioned with matches"
+    );
+
+    let none = trigger(json!({ "pattern": "nothing-*", "dry_run": true }))?;
+    assert_eq!(none.status, 400);
+
+    let invalid = trigger(json!({ "pattern": "/(/", "dry_run": true }))?;
+    assert_eq!(invalid.status, 400);
+
+    Ok(())
+}
+
+async fn scenario_import_self_update_reports_cli() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let report_dir = env.state_dir.join("self-update-reports");
+    fs::create_dir_all(&report_dir)?;
+    let report = json!({
+        "type": "self-update-run",
+        "started_at": 1_700_000_000,
+        "finished_at": 1_700_000_030,
+        "status": "succeeded",
+        "exit_code": 0,
+        "dry_run": true,
+        "release_tag": "v9.9.9",
+    })
+    .to_string();
+    fs::write(report_dir.join("report-1.json"), &report)?;
+    fs::write(report_dir.join("notes.txt"), "ignored")?;
+
+    let run_import = |extra: &[&str]| -> AnyResult<CommandResult> {
+        let mut cmd = env.command();
+        cmd.arg("import-self-update-reports")
+            .args(extra)
+            .env("PODUP_SELF_UPDATE_REPORT_DIR", &report_dir);
+        env.run_command(cmd)
+    };
+
+    let first = run_import(&[])?;
+    assert!(first.status.success(), "stderr: {}", first.stderr);
+    assert!(first.stdout.contains("reports=1"), "{}", first.stdout);
+    assert!(report_dir.join("report-1.json.imported").exists());
+
+    // The same report showing up again must not create a second task.
+    fs::write(report_dir.join("report-1.json"), &report)?;
+    let second = run_import(&[])?;
+    assert!(second.status.success(), "stderr: {}", second.stderr);
+    assert!(second.stdout.contains("duplicates=1"), "{}", second.stdout);
+
+    let pool = env.connect_db().await?;
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tasks WHERE kind = 'self-update'")
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(count, 1);
+
+    // Reports older than --since are left alone.
+    fs::write(report_dir.join("report-2.json"), &report)?;
+    let skipped = run_import(&["--since", "4102444800"])?;
+    assert!(skipped.status.success(), "stderr: {}", skipped.stderr);
+    assert!(skipped.stdout.contains("scanned=0"), "{}", skipped.stdout);
+    assert!(report_dir.join("report-2.json").exists());
+
+    Ok(())
+}
+
+async fn scenario_validate_config() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+
+    let ok = env.run_command({
+        let mut cmd = env.command();
+        cmd.arg("validate");
+        cmd
+    })?;
+    assert!(ok.status.success(), "validate should pass: {}", ok.stdout);
+    assert!(ok.stdout.contains("PODUP_STATE_DIR"));
+    assert!(ok.stdout.contains("0 error(s)"));
+    assert_eq!(
+        fs::metadata(&env.db_path)?.len(),
+        0,
+        "validate must not migrate or write the database"
+    );
+
+    let script = env.state_dir.join("self-update.sh");
+    fs::write(&script, b"#!/bin/sh\n")?;
+    let bad = env.run_command({
+        let mut cmd = env.command();
+        cmd.arg("validate");
+        cmd.env("PODUP_ENV", "prod");
+        cmd.env("PODUP_DEV_OPEN_ADMIN", "0");
+        cmd.env("PODUP_MANUAL_UNITS", "svc-alpha.service,bad unit!.service");
+        cmd.env("PODUP_SELF_UPDATE_COMMAND", &script);
+        cmd.env("PODUP_SELF_UPDATE_CRON", "15 3 * * 1");
+        cmd
+    })?;
+    assert_eq!(bad.status.code(), Some(1), "stdout: {}", bad.stdout);
+    let error_keys: Vec<&str> = bad
+        .stdout
+        .lines()
+        .filter(|line| line.starts_with("error"))
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .collect();
+    for key in [
+        "PODUP_FWD_AUTH_HEADER",
+        "PODUP_FWD_AUTH_ADMIN_VALUE",
+        "PODUP_MANUAL_UNITS",
+        "PODUP_SELF_UPDATE_CRON",
+    ] {
+        assert!(
+            error_keys.contains(&key),
+            "expected {key} error in report: {}",
+            bad.stdout
+        );
+    }
+
+    Ok(())
+}
+
 async fn scenario_auto_discovery() -> AnyResult<()> {
     let env = TestEnv::new()?;
     env.clear_mock_log()?;
@@ -281,6 +647,328 @@ async fn scenario_auto_discovery() -> AnyResult<()> {
     Ok(())
 }
 
+async fn scenario_read_only_mode() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+    env.clear_mock_log()?;
+
+    let trigger = || {
+        HttpRequest::post("/api/manual/trigger")
+            .header("x-podup-csrf", "1")
+            .header("content-type", "application/json")
+            .body(
+                json!({ "all": true, "dry_run": true, "caller": "ci", "reason": "ro" })
+                    .to_string()
+                    .into_bytes(),
+            )
+    };
+
+    // Forced through the environment.
+    let forced = env.send_request_with_env(trigger(), |cmd| {
+        cmd.env("PODUP_READ_ONLY", "1");
+    })?;
+    assert_eq!(forced.status, 503);
+    assert!(forced.headers.contains_key("retry-after"));
+    let health = env.send_request_with_env(HttpRequest::get("/health"), |cmd| {
+        cmd.env("PODUP_READ_ONLY", "1");
+    })?;
+    assert_ne!(health.status, 503, "{}", health.body_text());
+    let tasks = env.send_request_with_env(HttpRequest::get("/api/tasks"), |cmd| {
+        cmd.env("PODUP_READ_ONLY", "1");
+    })?;
+    assert_eq!(tasks.status, 200);
+    let cannot_disable = env.send_request_with_env(
+        HttpRequest::post("/api/maintenance")
+            .header("x-podup-csrf", "1")
+            .header("content-type", "application/json")
+            .body(json!({ "enabled": false }).to_string().into_bytes()),
+        |cmd| {
+            cmd.env("PODUP_READ_ONLY", "1");
+        },
+    )?;
+    assert_eq!(cannot_disable.status, 409);
+
+    // Admin toggle.
+    let enable = env.send_request(
+        HttpRequest::post("/api/maintenance")
+            .header("x-podup-csrf", "1")
+            .header("content-type", "application/json")
+            .body(
+                json!({ "enabled": true, "reason": "db migration" })
+                    .to_string()
+                    .into_bytes(),
+            ),
+    )?;
+    assert_eq!(enable.status, 200, "{}", enable.body_text());
+    let state = env
+        .send_request(HttpRequest::get("/api/maintenance"))?
+        .json_body()?;
+    assert_eq!(state["enabled"], true);
+    assert_eq!(state["source"], "admin");
+    assert_eq!(state["reason"], "db migration");
+
+    let blocked = env.send_request(trigger())?;
+    assert_eq!(blocked.status, 503);
+
+    let discover =
+        env.send_request(HttpRequest::post("/api/units/discover").header("x-podup-csrf", "1"))?;
+    assert_eq!(discover.status, 503, "{}", discover.body_text());
+
+    // The scheduler can still be paused and resumed while read-only.
+    for path in ["/api/scheduler/pause", "/api/scheduler/resume"] {
+        let response = env.send_request(
+            HttpRequest::post(path)
+                .header("x-podup-csrf", "1")
+                .header("content-type", "application/json")
+                .body(json!({}).to_string().into_bytes()),
+        )?;
+        assert_eq!(response.status, 200, "{path}: {}", response.body_text());
+    }
+
+    // Auth and CSRF failures keep their own status instead of a 503.
+    let unauthenticated = env.send_request_with_env(trigger(), |cmd| {
+        cmd.env("PODUP_DEV_OPEN_ADMIN", "0");
+        cmd.env("PODUP_FWD_AUTH_HEADER", "x-test-admin");
+        cmd.env("PODUP_FWD_AUTH_ADMIN_VALUE", "yes");
+    })?;
+    assert_eq!(
+        unauthenticated.status,
+        401,
+        "{}",
+        unauthenticated.body_text()
+    );
+    assert!(!unauthenticated.headers.contains_key("retry-after"));
+    let missing_csrf = env.send_request(
+        HttpRequest::post("/api/manual/trigger")
+            .header("content-type", "application/json")
+            .body(json!({ "all": true }).to_string().into_bytes()),
+    )?;
+    assert_eq!(missing_csrf.status, 403, "{}", missing_csrf.body_text());
+
+    // Dry runs never write, so they keep working.
+    let self_update_test = env.send_request_with_env(
+        HttpRequest::post("/api/self-update/test").header("x-podup-csrf", "1"),
+        |cmd| {
+            cmd.env("PODUP_SELF_UPDATE_COMMAND", "/bin/true");
+        },
+    )?;
+    assert_eq!(
+        self_update_test.status,
+        200,
+        "{}",
+        self_update_test.body_text()
+    );
+
+    // Scheduler ticks are deferred rather than dispatching work.
+    let mut scheduler = env.command();
+    scheduler
+        .arg("scheduler")
+        .arg("--interval")
+        .arg("1")
+        .arg("--max-iterations")
+        .arg("1");
+    let output = env.run_command(scheduler)?;
+    assert!(output.status.success(), "stderr: {}", output.stderr);
+    let pool = env.connect_db().await?;
+    let scheduler_tasks: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM tasks WHERE trigger_source = 'scheduler'")
+            .fetch_one(&pool)
+            .await?;
+    assert_eq!(scheduler_tasks, 0);
+    let events = env.fetch_events(&pool).await?;
+    assert!(events.iter().any(|row| row.action == "scheduler"
+        && row.meta["status"] == "skipped"
+        && row.meta["reason"] == "maintenance"));
+
+    // Verified webhooks are rejected as retriable and not remembered.
+    let payload = github_registry_payload("koha", "svc-alpha", "main");
+    let signature = env.github_signature(&payload);
+    let webhook = || {
+        HttpRequest::post("/github-package-update/svc-alpha")
+            .header("x-github-event", "registry_package")
+            .header("x-github-delivery", "ro-delivery")
+            .header("x-hub-signature-256", &signature)
+            .body(payload.clone())
+    };
+    let rejected = env.send_request(webhook())?;
+    assert_eq!(rejected.status, 503);
+
+    let preview = env.send_request(
+        HttpRequest::post("/github-package-update/svc-alpha?dry_run=1")
+            .header("x-github-event", "registry_package")
+            .header("x-github-delivery", "ro-preview")
+            .header("x-hub-signature-256", &signature)
+            .body(payload.clone()),
+    )?;
+    assert_eq!(preview.status, 200, "{}", preview.body_text());
+    assert_eq!(preview.json_body()?["dry_run"], true);
+    let unsigned = env.send_request(
+        HttpRequest::post("/github-package-update/svc-alpha")
+            .header("x-github-event", "registry_package")
+            .header("x-github-delivery", "ro-unsigned")
+            .header("x-hub-signature-256", "sha256=deadbeef")
+            .body(payload.clone()),
+    )?;
+    assert_eq!(unsigned.status, 401, "{}", unsigned.body_text());
+
+    let events = env.fetch_events(&pool).await?;
+    assert!(events.iter().any(|row| row.action == "github-webhook"
+        && row.status == 503
+        && row.meta["reason"] == "maintenance"));
+
+    let disable = env.send_request(
+        HttpRequest::post("/api/maintenance")
+            .header("x-podup-csrf", "1")
+            .header("content-type", "application/json")
+            .body(json!({ "enabled": false }).to_string().into_bytes()),
+    )?;
+    assert_eq!(disable.status, 200);
+    assert_eq!(disable.json_body()?["enabled"], false);
+
+    let allowed = env.send_request(trigger())?;
+    assert_eq!(allowed.status, 202, "{}", allowed.body_text());
+    let redelivered = env.send_request_with_env(webhook(), |cmd| {
+        configure_image_verify_mocks(cmd);
+    })?;
+    assert_eq!(redelivered.status, 202, "{}", redelivered.body_text());
+
+    Ok(())
+}
+
+async fn scenario_db_backup() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+    let pool = env.connect_db().await?;
+    let task_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tasks")
+        .fetch_one(&pool)
+        .await?;
+
+    let response = env.send_request(HttpRequest::get("/api/backup/db"))?;
+    assert_eq!(response.status, 200, "{}", response.body_text());
+    assert_eq!(
+        response.headers.get("content-type").map(String::as_str),
+        Some("application/vnd.sqlite3")
+    );
+    assert!(
+        response
+            .headers
+            .get("content-disposition")
+            .is_some_and(|value| value.starts_with("attachment;")),
+        "backup should be served as an attachment: {:?}",
+        response.headers
+    );
+    assert!(response.body.starts_with(b"SQLite format 3\0"));
+
+    // The snapshot is a complete, openable database.
+    let restored = env.state_dir.join("restored.sqlite");
+    fs::write(&restored, &response.body)?;
+    let restored_pool = SqlitePool::connect(&format!("sqlite://{}", restored.display())).await?;
+    let restored_tasks: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tasks")
+        .fetch_one(&restored_pool)
+        .await?;
+    assert_eq!(restored_tasks, task_count);
+    restored_pool.close().await;
+
+    let backup_dir = env.state_dir.join("backups");
+    let leftovers: Vec<_> = fs::read_dir(&backup_dir)?
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("snapshot-"))
+        .collect();
+    assert!(
+        leftovers.is_empty(),
+        "snapshots should be removed after streaming"
+    );
+
+    let too_large = env.send_request_with_env(HttpRequest::get("/api/backup/db"), |cmd| {
+        cmd.env("PODUP_BACKUP_MAX_BYTES", "1");
+    })?;
+    assert_eq!(too_large.status, 413);
+
+    // A backup already holding the lock makes the next one back off.
+    let lock = File::create(backup_dir.join("db-backup.lock"))?;
+    {
+        use std::os::unix::io::AsRawFd;
+        // SAFETY: `lock` is a valid open file for the duration of the call.
+        assert_eq!(unsafe { libc::flock(lock.as_raw_fd(), libc::LOCK_EX) }, 0);
+    }
+    let busy = env.send_request(HttpRequest::get("/api/backup/db"))?;
+    assert_eq!(busy.status, 409);
+    drop(lock);
+
+    Ok(())
+}
+
+async fn scenario_units_status() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+    env.clear_mock_log()?;
+
+    let ps_json = json!([
+        {
+            "Id": "cid-alpha-old",
+            "Created": 900,
+            "State": "exited",
+            "ImageID": "img-alpha-old",
+            "Labels": { "io.podman.systemd.unit": "svc-alpha.service" }
+        },
+        {
+            "Id": "cid-alpha",
+            "Created": 1000,
+            "State": "running",
+            "ImageID": "img-alpha",
+            "Labels": { "io.podman.systemd.unit": "svc-alpha.service" }
+        },
+        {
+            "Id": "cid-beta",
+            "Created": 1000,
+            "State": "exited",
+            "ImageID": "img-beta",
+            "Labels": { "io.podman.systemd.unit": "svc-beta.service" }
+        }
+    ]);
+    let inspect_json = json!([
+        { "Id": "img-alpha", "RepoDigests": ["ghcr.io/koha/svc-alpha@sha256:aaa"] },
+        { "Id": "img-beta", "Digest": "sha256:bbb" }
+    ]);
+
+    let response = env.send_request_with_env(HttpRequest::get("/api/units/status"), |cmd| {
+        cmd.env("MOCK_PODMAN_PS_JSON", ps_json.to_string());
+        cmd.env("MOCK_PODMAN_IMAGE_INSPECT_JSON", inspect_json.to_string());
+    })?;
+    assert_eq!(response.status, 200, "{}", response.body_text());
+    let body = response.json_body()?;
+    let units = body["units"].as_array().cloned().unwrap_or_default();
+    let find = |unit: &str| {
+        units
+            .iter()
+            .find(|entry| entry["unit"] == unit)
+            .cloned()
+            .unwrap_or(Value::Null)
+    };
+
+    let alpha = find("svc-alpha.service");
+    assert_eq!(alpha["running_digest"], "sha256:aaa");
+    assert_eq!(alpha["container_state"], "running");
+    assert!(alpha["reason"].is_null());
+
+    let beta = find("svc-beta.service");
+    assert_eq!(beta["running_digest"], "sha256:bbb");
+    assert_eq!(beta["container_state"], "exited");
+
+    // All units are resolved with a single image inspect call.
+    let log = env.read_mock_log()?;
+    let inspects = log
+        .iter()
+        .filter(|line| line.contains("podman image inspect"))
+        .count();
+    assert_eq!(inspects, 1, "mock log: {log:?}");
+
+    let method = env.send_request(HttpRequest::post("/api/units/status"))?;
+    assert_eq!(method.status, 405);
+
+    Ok(())
+}
+
 async fn scenario_auto_discovery_podman_ps_skips_missing_unit_label() -> AnyResult<()> {
     let env = TestEnv::new()?;
     env.ensure_db_initialized().await?;
@@ -415,8 +1103,208 @@ async fn scenario_health_db_error() -> AnyResult<()> {
             .any(|issue| issue["component"] == Value::from("database"))
     );
 
-    Ok(())
-}
+    // The process itself is fine: liveness stays green while readiness
+    // reports the broken database.
+    let live = env.send_request_with_env(HttpRequest::get("/health/live"), |cmd| {
+        cmd.env("PODUP_DB_URL", "postgres://forbidden/uri");
+    })?;
+    assert_eq!(live.status, 200);
+    assert_eq!(live.json_body()?["status"], "ok");
+
+    let ready = env.send_request_with_env(HttpRequest::get("/health/ready"), |cmd| {
+        cmd.env("PODUP_DB_URL", "postgres://forbidden/uri");
+    })?;
+    assert_eq!(ready.status, 503);
+    let json = ready.json_body()?;
+    assert_eq!(json["status"], "not-ready");
+    assert!(
+        json["issues"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|issue| issue["component"] == "database")
+    );
+
+    let healthz = env.send_request_with_env(HttpRequest::get("/healthz"), |cmd| {
+        cmd.env("PODUP_DB_URL", "postgres://forbidden/uri");
+    })?;
+    assert_eq!(healthz.status, 503);
+    assert_eq!(healthz.body_text(), "NOT READY");
+
+    Ok(())
+}
+
+async fn scenario_health_disk_space() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let response = env.send_request(HttpRequest::get("/health"))?;
+    assert_eq!(response.status, 200);
+    let json = response.json_body()?;
+    let volumes = json["disk"]["volumes"].as_array().expect("disk volumes");
+    assert!(
+        volumes.iter().any(|volume| volume["target"] == "state_dir"
+            && volume["free_bytes"].as_u64().is_some()
+            && volume["status"] == "ok"),
+        "state dir volume reported: {json}"
+    );
+
+    // A 100% threshold can never be met, so the disk must be flagged while
+    // the endpoint itself stays healthy.
+    let response = env.send_request_with_env(HttpRequest::get("/health"), |cmd| {
+        cmd.env("PODUP_HEALTH_DISK_MIN_FREE_PERCENT", "100");
+    })?;
+    assert_eq!(response.status, 200);
+    let json = response.json_body()?;
+    assert_eq!(json["status"], Value::from("degraded"));
+    let issues = json["issues"].as_array().unwrap();
+    assert!(
+        issues
+            .iter()
+            .any(|issue| issue["component"] == "disk" && issue["severity"] == "warning"),
+        "disk warning issue: {json}"
+    );
+    assert!(
+        json["disk"]["volumes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|volume| volume["status"] == "degraded")
+    );
+
+    // Disk warnings do not take the service out of rotation.
+    let ready = env.send_request_with_env(HttpRequest::get("/health/ready"), |cmd| {
+        cmd.env("PODUP_HEALTH_DISK_MIN_FREE_PERCENT", "100");
+    })?;
+    assert_eq!(ready.status, 200);
+    let json = ready.json_body()?;
+    assert_eq!(json["status"], "ready");
+    assert_eq!(json["issues"], json!([]));
+
+    let healthz = env.send_request_with_env(HttpRequest::get("/healthz"), |cmd| {
+        cmd.env("PODUP_HEALTH_DISK_MIN_FREE_PERCENT", "100");
+    })?;
+    assert_eq!(healthz.status, 200);
+    assert_eq!(healthz.body_text(), "OK");
+
+    Ok(())
+}
+
+async fn scenario_health_systemd() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let response = env.send_request(HttpRequest::get("/health"))?;
+    assert_eq!(response.status, 200);
+    let json = response.json_body()?;
+    assert_eq!(json["systemd"]["ok"], Value::from(true));
+    assert_eq!(json["systemd"]["status"], "ok");
+    assert_eq!(json["systemd"]["state"], "running");
+
+    // Failed units leave the manager reachable: warn but stay healthy.
+    let response = env.send_request_with_env(HttpRequest::get("/health"), |cmd| {
+        cmd.env("MOCK_SYSTEMCTL_SYSTEM_STATE", "degraded");
+    })?;
+    assert_eq!(response.status, 200);
+    let json = response.json_body()?;
+    assert_eq!(json["systemd"]["status"], "degraded");
+    assert_eq!(json["systemd"]["state"], "degraded");
+    assert!(
+        json["issues"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|issue| issue["component"] == "systemd" && issue["severity"] == "warning"),
+        "systemd warning issue: {json}"
+    );
+
+    // An unreachable user manager means tasks can never run.
+    let response = env.send_request_with_env(HttpRequest::get("/health"), |cmd| {
+        cmd.env("MOCK_SYSTEMCTL_SYSTEM_STATE", "bus-error");
+    })?;
+    assert_eq!(response.status, 503);
+    let json = response.json_body()?;
+    assert_eq!(json["systemd"]["ok"], Value::from(false));
+    assert!(
+        json["systemd"]["error"]
+            .as_str()
+            .is_some_and(|err| err.contains("Failed to connect to bus")),
+        "systemd error: {json}"
+    );
+
+    Ok(())
+}
+
+async fn scenario_github_webhook_form_encoded() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.clear_mock_log()?;
+
+    // GitHub's form content type wraps the JSON in a `payload=` field and
+    // signs the encoded body as sent.
+    let payload = github_registry_payload("koha", "svc-alpha", "main");
+    let form_body = url::form_urlencoded::Serializer::new(String::new())
+        .append_pair("payload", std::str::from_utf8(&payload)?)
+        .finish()
+        .into_bytes();
+    let signature = env.github_signature(&form_body);
+    let response = env.send_request_with_env(
+        HttpRequest::post("/github-package-update/svc-alpha")
+            .header("content-type", "application/x-www-form-urlencoded")
+            .header("x-github-event", "registry_package")
+            .header("x-github-delivery", "form-delivery")
+            .header("x-hub-signature-256", &signature)
+            .body(form_body),
+        |cmd| {
+            configure_image_verify_mocks(cmd);
+        },
+    )?;
+    assert_eq!(
+        response.status,
+        202,
+        "form-encoded webhook accepted: {}",
+        response.body_text()
+    );
+    let log_lines = env.read_mock_log()?;
+    assert!(
+        log_lines
+            .iter()
+            .any(|line| line.contains("podman pull ghcr.io/koha/svc-alpha:main")),
+        "form-encoded payload should resolve the image: {log_lines:?}"
+    );
+
+    // A signature over the decoded JSON does not match the form body.
+    let form_body = format!(
+        "payload={}",
+        url::form_urlencoded::byte_serialize(&payload).collect::<String>()
+    )
+    .into_bytes();
+    let response = env.send_request(
+        HttpRequest::post("/github-package-update/svc-alpha")
+            .header("content-type", "application/x-www-form-urlencoded")
+            .header("x-github-event", "registry_package")
+            .header("x-github-delivery", "form-delivery-2")
+            .header("x-hub-signature-256", &env.github_signature(&payload))
+            .body(form_body),
+    )?;
+    assert_eq!(response.status, 401);
+
+    // Form bodies without a payload field are rejected after verification.
+    let form_body = b"other=1".to_vec();
+    let response = env.send_request(
+        HttpRequest::post("/github-package-update/svc-alpha")
+            .header(
+                "content-type",
+                "application/x-www-form-urlencoded; charset=utf-8",
+            )
+            .header("x-github-event", "registry_package")
+            .header("x-github-delivery", "form-delivery-3")
+            .header("x-hub-signature-256", &env.github_signature(&form_body))
+            .body(form_body),
+    )?;
+    assert_eq!(response.status, 400, "{}", response.body_text());
+
+    Ok(())
+}
 
 async fn scenario_github_webhook() -> AnyResult<()> {
     let env = TestEnv::new()?;
@@ -483,11 +1371,58 @@ async fn scenario_github_webhook() -> AnyResult<()> {
         .await?;
     assert_eq!(lock_count, 0);
 
+    // A GitHub redelivery of the same id points back at the original task.
+    let original_task: String =
+        sqlx::query_scalar("SELECT task_id FROM webhook_deliveries WHERE delivery = 'delivery-42'")
+            .fetch_one(&pool)
+            .await?;
+    let redelivery = env.send_request_with_env(
+        HttpRequest::post("/github-package-update/svc-alpha")
+            .header("x-github-event", "registry_package")
+            .header("x-github-delivery", "delivery-42")
+            .header("x-hub-signature-256", &signature)
+            .body(payload.clone()),
+        |cmd| {
+            configure_image_verify_mocks(cmd);
+        },
+    )?;
+    assert_eq!(redelivery.status, 200, "{}", redelivery.body_text());
+    assert_eq!(redelivery.body_text().trim(), "idempotent-replay");
+    let task_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tasks")
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(task_count, 1);
+    let replay_event = env
+        .fetch_events(&pool)
+        .await?
+        .into_iter()
+        .find(|event| event.action == "github-webhook" && event.status == 200)
+        .expect("idempotent replay event stored");
+    assert_eq!(
+        replay_event.meta.get("task_id").and_then(|v| v.as_str()),
+        Some(original_task.as_str())
+    );
+
+    // With the window disabled the same delivery queues a new task.
+    let response = env.send_request_with_env(
+        HttpRequest::post("/github-package-update/svc-alpha")
+            .header("x-github-event", "registry_package")
+            .header("x-github-delivery", "delivery-42")
+            .header("x-hub-signature-256", &signature)
+            .body(payload.clone()),
+        |cmd| {
+            configure_image_verify_mocks(cmd);
+            cmd.env("PODUP_WEBHOOK_DEDUP_WINDOW_SECS", "0");
+        },
+    )?;
+    assert_eq!(response.status, 202, "{}", response.body_text());
+
     Ok(())
 }
 
-async fn scenario_webhook_image_prune_success() -> AnyResult<()> {
+async fn scenario_task_executor_by_kind() -> AnyResult<()> {
     let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
     env.clear_mock_log()?;
 
     let payload = github_registry_payload("koha", "svc-alpha", "main");
@@ -495,98 +1430,490 @@ async fn scenario_webhook_image_prune_success() -> AnyResult<()> {
     let response = env.send_request_with_env(
         HttpRequest::post("/github-package-update/svc-alpha")
             .header("x-github-event", "registry_package")
-            .header("x-github-delivery", "prune-ok")
+            .header("x-github-delivery", "executor-by-kind")
             .header("x-hub-signature-256", &signature)
-            .body(payload.clone()),
+            .body(payload),
         |cmd| {
             configure_image_verify_mocks(cmd);
+            cmd.env("PODUP_TASK_EXECUTOR", "systemd-run");
+            cmd.env(
+                "PODUP_TASK_EXECUTOR_BY_KIND",
+                "github-webhook=local-child,maintenance=systemd-run",
+            );
         },
     )?;
-    assert_eq!(
-        response.status,
-        202,
-        "github webhook with prune-ok delivery should be accepted: {}",
-        response.body_text()
-    );
+    assert_eq!(response.status, 202, "{}", response.body_text());
 
+    // The local child runs detached; wait for it so its host commands do not
+    // leak into the shared mock log of later scenarios.
     let pool = env.connect_db().await?;
-    let events = env.fetch_events(&pool).await?;
-    let task_id = events
-        .iter()
-        .find(|row| row.action == "github-webhook")
-        .and_then(|row| row.meta.get("task_id"))
-        .and_then(|v| v.as_str())
-        .unwrap_or_default()
-        .to_string();
-    assert!(
-        !task_id.is_empty(),
-        "github-webhook events should include a task_id in meta for prune success scenario"
-    );
-
-    let path = format!("/api/tasks/{task_id}");
-    let detail_resp = env.send_request(HttpRequest::get(&path))?;
-    assert_eq!(
-        detail_resp.status, 200,
-        "/api/tasks/:id should succeed for prune success scenario"
-    );
-    let body = detail_resp.json_body()?;
-    assert_eq!(
-        body["status"],
-        Value::from("succeeded"),
-        "webhook task should be marked succeeded when prune succeeds, got: {}",
-        body["status"]
-    );
-
-    let logs = body["logs"].as_array().cloned().unwrap_or_default();
-    let prune_log = logs
-        .iter()
-        .find(|entry| {
-            entry.get("action") == Some(&Value::from("image-prune"))
-                && entry.get("status") == Some(&Value::from("succeeded"))
-        })
-        .cloned()
-        .expect("expected image-prune log entry with status=succeeded for webhook prune success");
+    let mut status = String::new();
+    for _ in 0..100 {
+        let row = sqlx::query("SELECT status FROM tasks WHERE kind = 'github-webhook' LIMIT 1")
+            .fetch_optional(&pool)
+            .await?;
+        if let Some(row) = row {
+            status = row.get::<String, _>("status");
+            if status != "running" && status != "pending" {
+                break;
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(status, "succeeded");
 
-    let meta = prune_log.get("meta").cloned().unwrap_or_else(|| json!({}));
-    assert_eq!(
-        meta.get("type").and_then(|v| v.as_str()),
-        Some("command"),
-        "image-prune meta.type should be 'command'"
+    let log_lines = env.read_mock_log()?;
+    assert!(
+        !log_lines.iter().any(|line| line.contains("systemd-run")),
+        "github-webhook tasks must bypass systemd-run: {log_lines:?}"
     );
-    let command = meta.get("command").and_then(|v| v.as_str()).unwrap_or("");
     assert!(
-        command.contains("podman image prune -f"),
-        "image-prune meta.command should contain 'podman image prune -f', got: {command}"
+        log_lines
+            .iter()
+            .any(|line| line.contains("podman pull ghcr.io/koha/svc-alpha:main")),
+        "local child ran the task: {log_lines:?}"
     );
 
     Ok(())
 }
 
-async fn scenario_webhook_image_prune_failure() -> AnyResult<()> {
+async fn scenario_webhook_replay() -> AnyResult<()> {
     let env = TestEnv::new()?;
     env.clear_mock_log()?;
 
+    let replay_request = |body: Value| {
+        HttpRequest::post("/api/webhooks/replay")
+            .header("content-type", "application/json")
+            .header("x-podup-csrf", "1")
+            .body(body.to_string().into_bytes())
+    };
+
+    let missing = env.send_request(replay_request(json!({ "unit": "svc-alpha" })))?;
+    assert_eq!(missing.status, 404, "{}", missing.body_text());
+
     let payload = github_registry_payload("koha", "svc-alpha", "main");
     let signature = env.github_signature(&payload);
-    let request = HttpRequest::post("/github-package-update/svc-alpha")
-        .header("x-github-event", "registry_package")
-        .header("x-github-delivery", "prune-fail")
-        .header("x-hub-signature-256", &signature)
-        .body(payload.clone());
+    let original = env.send_request_with_env(
+        HttpRequest::post("/github-package-update/svc-alpha")
+            .header("x-github-event", "registry_package")
+            .header("x-github-delivery", "replay-source")
+            .header("x-hub-signature-256", &signature)
+            .body(payload.clone()),
+        |cmd| {
+            configure_image_verify_mocks(cmd);
+        },
+    )?;
+    assert_eq!(original.status, 202, "{}", original.body_text());
 
-    let response = env.send_request_with_env(request, |cmd| {
+    let forged = env.send_request(
+        HttpRequest::post("/github-package-update/svc-alpha")
+            .header("x-github-event", "registry_package")
+            .header("x-github-delivery", "forged-delivery")
+            .header("x-hub-signature-256", "sha256=deadbeef")
+            .body(payload.clone()),
+    )?;
+    assert_eq!(forged.status, 401);
+
+    // A forged body reusing a verified delivery id is kept apart from it.
+    let hijack = env.send_request(
+        HttpRequest::post("/github-package-update/svc-alpha")
+            .header("x-github-event", "registry_package")
+            .header("x-github-delivery", "replay-source")
+            .header("x-hub-signature-256", "sha256=deadbeef")
+            .body(b"{}".to_vec()),
+    )?;
+    assert_eq!(hijack.status, 401);
+
+    let listing = env.send_request(HttpRequest::get("/api/webhooks/payloads"))?;
+    assert_eq!(listing.status, 200, "{}", listing.body_text());
+    let listing = listing.json_body()?;
+    assert_eq!(listing["limit"], 20);
+    let payloads = listing["payloads"].as_array().cloned().unwrap_or_default();
+    assert_eq!(payloads.len(), 3, "{listing}");
+    let source = payloads
+        .iter()
+        .find(|p| p["delivery"] == "replay-source" && p["size"] == payload.len() as u64)
+        .expect("verified delivery stored");
+    assert_eq!(source["signature_valid"], true);
+    assert_eq!(source["unit"], "svc-alpha.service");
+    assert_eq!(source["size"], payload.len() as u64);
+
+    let download = env.send_request(HttpRequest::get("/api/webhooks/payloads/replay-source"))?;
+    assert_eq!(download.status, 200);
+    assert_eq!(download.body, payload);
+    let unknown = env.send_request(HttpRequest::get("/api/webhooks/payloads/nope"))?;
+    assert_eq!(unknown.status, 404);
+
+    // Rejected bodies are only kept as a short prefix next to the metadata.
+    let oversized = vec![b'x'; 64 * 1024];
+    let flood = env.send_request(
+        HttpRequest::post("/github-package-update/svc-alpha")
+            .header("x-github-event", "registry_package")
+            .header("x-github-delivery", "oversized-forgery")
+            .header("x-hub-signature-256", "sha256=deadbeef")
+            .body(oversized.clone()),
+    )?;
+    assert_eq!(flood.status, 401);
+    let rejected_dir = env.state_dir.join("webhook-payloads/rejected");
+    let stored = fs::read(rejected_dir.join("oversized-forgery.bin"))?;
+    assert_eq!(stored, oversized[..4096]);
+    let sidecar: Value =
+        serde_json::from_slice(&fs::read(rejected_dir.join("oversized-forgery.json"))?)?;
+    assert_eq!(sidecar["size"], oversized.len() as u64);
+    assert_eq!(sidecar["truncated"], true);
+
+    let forged_replay =
+        env.send_request(replay_request(json!({ "delivery": "forged-delivery" })))?;
+    assert_eq!(forged_replay.status, 403, "{}", forged_replay.body_text());
+
+    // Without a delivery id the newest verified delivery is replayed; the
+    // newer forged body never is.
+    let latest = env.send_request_with_env(replay_request(json!({})), |cmd| {
         configure_image_verify_mocks(cmd);
-        cmd.env("MOCK_PODMAN_PRUNE_FAIL", "1");
     })?;
-    assert_eq!(
-        response.status,
-        202,
-        "github webhook with failing prune should still be accepted: {}",
-        response.body_text()
+    assert_eq!(latest.status, 202, "{}", latest.body_text());
+    assert_eq!(latest.json_body()?["replay_of"], "replay-source");
+
+    env.clear_mock_log()?;
+    let replayed = env.send_request_with_env(
+        replay_request(json!({ "delivery": "replay-source" })),
+        |cmd| {
+            configure_image_verify_mocks(cmd);
+        },
+    )?;
+    assert_eq!(replayed.status, 202, "{}", replayed.body_text());
+    let replayed = replayed.json_body()?;
+    assert_eq!(replayed["replay"], true);
+    assert_eq!(replayed["replay_of"], "replay-source");
+    assert_eq!(replayed["unit"], "svc-alpha.service");
+    let task_id = replayed["task_id"].as_str().unwrap_or_default().to_string();
+    assert!(!task_id.is_empty(), "replay returns task id: {replayed}");
+    let delivery = replayed["delivery"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    assert_ne!(delivery, "replay-source");
+    assert!(
+        env.read_mock_log()?
+            .iter()
+            .any(|line| line.contains(&format!(
+                "--unit=webhook-task-{}",
+                delivery.to_ascii_lowercase()
+            ))),
+        "replayed delivery dispatched"
     );
 
     let pool = env.connect_db().await?;
-    let events = env.fetch_events(&pool).await?;
+    let (reason, meta): (Option<String>, String) =
+        sqlx::query_as("SELECT trigger_reason, meta FROM tasks WHERE task_id = ?")
+            .bind(&task_id)
+            .fetch_one(&pool)
+            .await?;
+    assert_eq!(reason.as_deref(), Some("replay"));
+    let meta: Value = serde_json::from_str(&meta)?;
+    assert_eq!(meta["replay"], true);
+
+    // Replays go through the same guards as fresh deliveries.
+    let rejected = env.send_request_with_env(
+        replay_request(json!({ "path": "/github-package-update/svc-alpha" })),
+        |cmd| {
+            cmd.env("PODUP_IMAGE_ALLOWLIST", "ghcr.io/trusted/");
+        },
+    )?;
+    assert_eq!(rejected.status, 403, "{}", rejected.body_text());
+    let rejected = rejected.json_body()?;
+    assert_eq!(rejected["reason"], "image-policy");
+    assert!(rejected["task_id"].is_null());
+
+    Ok(())
+}
+
+async fn scenario_manual_service_simulate_webhook() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+    env.clear_mock_log()?;
+
+    let container_dir = env.state_dir.join("containers/systemd");
+    fs::create_dir_all(&container_dir)?;
+    fs::write(
+        container_dir.join("svc-alpha.container"),
+        b"[Container]\nImage=ghcr.io/koha/svc-alpha:latest\n",
+    )?;
+
+    let simulate = |body: Value| {
+        HttpRequest::post("/api/manual/services/svc-alpha/simulate-webhook")
+            .header("content-type", "application/json")
+            .header("x-podup-csrf", "1")
+            .body(body.to_string().into_bytes())
+    };
+
+    let preview = env.send_request_with_env(simulate(json!({ "dry_run": true })), |cmd| {
+        cmd.env("PODUP_CONTAINER_DIR", &container_dir);
+    })?;
+    assert_eq!(preview.status, 200, "{}", preview.body_text());
+    let preview = preview.json_body()?;
+    assert_eq!(preview["simulated"], true);
+    assert_eq!(preview["mapped_unit"], "svc-alpha.service");
+    assert_eq!(preview["image"], "ghcr.io/koha/svc-alpha:latest");
+    assert_eq!(preview["image_matches"], true);
+    assert_eq!(preview["would_queue"], true);
+    assert!(
+        env.read_mock_log()?
+            .iter()
+            .all(|line| !line.contains("systemd-run")),
+        "dry-run simulation must not dispatch"
+    );
+
+    let response = env.send_request_with_env(simulate(json!({})), |cmd| {
+        cmd.env("PODUP_CONTAINER_DIR", &container_dir);
+        configure_image_verify_mocks(cmd);
+    })?;
+    assert_eq!(response.status, 202, "{}", response.body_text());
+    let body = response.json_body()?;
+    assert_eq!(body["simulated"], true);
+    assert_eq!(body["unit"], "svc-alpha.service");
+    assert_eq!(body["image"], "ghcr.io/koha/svc-alpha:latest");
+    let task_id = body["task_id"].as_str().unwrap_or_default().to_string();
+    assert!(!task_id.is_empty(), "simulation returns task id: {body}");
+    let delivery = body["delivery"].as_str().unwrap_or_default().to_string();
+    assert!(
+        env.read_mock_log()?
+            .iter()
+            .any(|line| line.contains(&format!(
+                "--unit=webhook-task-{}",
+                delivery.to_ascii_lowercase()
+            ))),
+        "simulated delivery dispatched"
+    );
+
+    let rejected = env.send_request_with_env(simulate(json!({})), |cmd| {
+        cmd.env("PODUP_CONTAINER_DIR", &container_dir);
+        cmd.env("PODUP_IMAGE_ALLOWLIST", "ghcr.io/trusted/");
+    })?;
+    assert_eq!(rejected.status, 403, "{}", rejected.body_text());
+    assert_eq!(rejected.json_body()?["reason"], "image-policy");
+
+    Ok(())
+}
+
+async fn scenario_github_image_policy() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.clear_mock_log()?;
+
+    let payload = github_registry_payload("koha", "svc-alpha", "main");
+    let signature = env.github_signature(&payload);
+    let response = env.send_request_with_env(
+        HttpRequest::post("/github-package-update/svc-alpha")
+            .header("x-github-event", "registry_package")
+            .header("x-github-delivery", "policy-allow")
+            .header("x-hub-signature-256", &signature)
+            .body(payload.clone()),
+        |cmd| {
+            cmd.env("PODUP_IMAGE_ALLOWLIST", "ghcr.io/trusted/");
+        },
+    )?;
+    assert_eq!(response.status, 403, "{}", response.body_text());
+
+    let response = env.send_request_with_env(
+        HttpRequest::post("/github-package-update/svc-alpha")
+            .header("x-github-event", "registry_package")
+            .header("x-github-delivery", "policy-deny")
+            .header("x-hub-signature-256", &signature)
+            .body(payload.clone()),
+        |cmd| {
+            cmd.env("PODUP_IMAGE_ALLOWLIST", "ghcr.io/koha/");
+            cmd.env("PODUP_IMAGE_DENYLIST", "ghcr.io/koha/svc-*");
+        },
+    )?;
+    assert_eq!(response.status, 403, "{}", response.body_text());
+
+    let log_lines = env.read_mock_log()?;
+    assert!(
+        log_lines.iter().all(|line| !line.contains("systemd-run")),
+        "rejected images must not dispatch tasks: {log_lines:?}"
+    );
+
+    let pool = env.connect_db().await?;
+    let events = env.fetch_events(&pool).await?;
+    let rejected: Vec<_> = events
+        .iter()
+        .filter(|row| row.action == "github-webhook" && row.status == 403)
+        .collect();
+    assert_eq!(rejected.len(), 2);
+    let lists: Vec<&str> = rejected
+        .iter()
+        .filter_map(|row| row.meta.get("list").and_then(|v| v.as_str()))
+        .collect();
+    assert!(lists.contains(&"allowlist") && lists.contains(&"denylist"));
+    assert!(rejected.iter().all(|row| {
+        row.meta.get("repository").and_then(|v| v.as_str()) == Some("ghcr.io/koha/svc-alpha")
+    }));
+
+    let task_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tasks")
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(task_count, 0);
+
+    Ok(())
+}
+
+async fn scenario_github_tag_filter() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.clear_mock_log()?;
+
+    let payload = github_registry_payload("koha", "svc-alpha", "main");
+    let signature = env.github_signature(&payload);
+    let response = env.send_request_with_env(
+        HttpRequest::post("/github-package-update/svc-alpha")
+            .header("x-github-event", "registry_package")
+            .header("x-github-delivery", "tag-filter-skip")
+            .header("x-hub-signature-256", &signature)
+            .body(payload.clone()),
+        |cmd| {
+            cmd.env("PODUP_UNIT_TAG_FILTER", r"svc-alpha=^v\d+\.\d+\.\d+$");
+        },
+    )?;
+    assert_eq!(response.status, 200, "{}", response.body_text());
+
+    let log_lines = env.read_mock_log()?;
+    assert!(
+        log_lines.iter().all(|line| !line.contains("systemd-run")),
+        "filtered tags must not dispatch tasks: {log_lines:?}"
+    );
+
+    let pool = env.connect_db().await?;
+    let task_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tasks")
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(task_count, 0);
+
+    let events = env.fetch_events(&pool).await?;
+    let skipped = events
+        .iter()
+        .find(|row| row.action == "github-webhook" && row.status == 200)
+        .expect("tag-filter event recorded");
+    assert_eq!(skipped.meta["reason"], "tag-filter");
+    assert_eq!(skipped.meta["tag"], "main");
+
+    let response = env.send_request_with_env(
+        HttpRequest::post("/github-package-update/svc-alpha")
+            .header("x-github-event", "registry_package")
+            .header("x-github-delivery", "tag-filter-pass")
+            .header("x-hub-signature-256", &signature)
+            .body(payload),
+        |cmd| {
+            cmd.env("PODUP_UNIT_TAG_FILTER", "svc-beta=^v; *=^main$");
+        },
+    )?;
+    assert_eq!(response.status, 202, "{}", response.body_text());
+
+    Ok(())
+}
+
+async fn scenario_github_webhook_preview() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.clear_mock_log()?;
+
+    let payload = github_registry_payload("koha", "svc-alpha", "main");
+    let signature = env.github_signature(&payload);
+    let response = env.send_request(
+        HttpRequest::post("/github-package-update/svc-alpha?dry_run=1")
+            .header("x-github-event", "registry_package")
+            .header("x-github-delivery", "preview-1")
+            .header("x-hub-signature-256", &signature)
+            .body(payload.clone()),
+    )?;
+    assert_eq!(response.status, 200, "{}", response.body_text());
+    let body = response.json_body()?;
+    assert_eq!(body["dry_run"], true);
+    assert_eq!(body["unit"], "svc-alpha.service");
+    assert_eq!(body["image"], "ghcr.io/koha/svc-alpha:main");
+    assert_eq!(body["signature"]["valid"], true);
+    assert_eq!(body["would_queue"], true);
+
+    let bad = env.send_request(
+        HttpRequest::post("/github-package-update/svc-alpha?dry_run=1")
+            .header("x-github-event", "registry_package")
+            .header("x-hub-signature-256", "sha256=deadbeef")
+            .body(payload.clone()),
+    )?;
+    assert_eq!(bad.status, 200);
+    let bad_body = bad.json_body()?;
+    assert_eq!(bad_body["signature"]["valid"], false);
+    assert_eq!(bad_body["would_queue"], false);
+    // Unit configuration and policy details stay hidden from unsigned callers.
+    for key in ["configured_image", "image_policy", "tag_filter"] {
+        assert!(bad_body.get(key).is_none(), "{key} leaked: {bad_body}");
+    }
+
+    // The preview applies the same tag filter as a real delivery.
+    let filtered = env.send_request_with_env(
+        HttpRequest::post("/github-package-update/svc-alpha?dry_run=1")
+            .header("x-github-event", "registry_package")
+            .header("x-github-delivery", "preview-2")
+            .header("x-hub-signature-256", &signature)
+            .body(payload.clone()),
+        |cmd| {
+            cmd.env("PODUP_UNIT_TAG_FILTER", "*=^v");
+        },
+    )?;
+    let filtered = filtered.json_body()?;
+    assert_eq!(filtered["tag_filter"]["allowed"], false, "{filtered}");
+    assert_eq!(filtered["would_queue"], false);
+
+    // Only read-only unit lookups are allowed; nothing is pulled or restarted.
+    let log_lines = env.read_mock_log()?;
+    assert!(
+        log_lines
+            .iter()
+            .all(|line| line.starts_with("systemctl --user show")),
+        "preview must not touch the host: {log_lines:?}"
+    );
+
+    let pool = env.connect_db().await?;
+    let task_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tasks")
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(task_count, 0);
+    let tokens: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM rate_limit_tokens")
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(tokens, 0);
+    let events = env.fetch_events(&pool).await?;
+    assert!(
+        events
+            .iter()
+            .any(|row| row.action == "github-webhook-preview" && row.status == 200)
+    );
+
+    Ok(())
+}
+
+async fn scenario_webhook_image_prune_success() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.clear_mock_log()?;
+
+    let payload = github_registry_payload("koha", "svc-alpha", "main");
+    let signature = env.github_signature(&payload);
+    let response = env.send_request_with_env(
+        HttpRequest::post("/github-package-update/svc-alpha")
+            .header("x-github-event", "registry_package")
+            .header("x-github-delivery", "prune-ok")
+            .header("x-hub-signature-256", &signature)
+            .body(payload.clone()),
+        |cmd| {
+            configure_image_verify_mocks(cmd);
+        },
+    )?;
+    assert_eq!(
+        response.status,
+        202,
+        "github webhook with prune-ok delivery should be accepted: {}",
+        response.body_text()
+    );
+
+    let pool = env.connect_db().await?;
+    let events = env.fetch_events(&pool).await?;
     let task_id = events
         .iter()
         .find(|row| row.action == "github-webhook")
@@ -596,99 +1923,178 @@ async fn scenario_webhook_image_prune_failure() -> AnyResult<()> {
         .to_string();
     assert!(
         !task_id.is_empty(),
-        "github-webhook events should include a task_id in meta for prune failure scenario"
+        "github-webhook events should include a task_id in meta for prune success scenario"
     );
 
     let path = format!("/api/tasks/{task_id}");
     let detail_resp = env.send_request(HttpRequest::get(&path))?;
     assert_eq!(
         detail_resp.status, 200,
-        "/api/tasks/:id should succeed for prune failure scenario"
+        "/api/tasks/:id should succeed for prune success scenario"
     );
     let body = detail_resp.json_body()?;
     assert_eq!(
         body["status"],
         Value::from("succeeded"),
-        "webhook task should remain succeeded even when prune fails, got: {}",
+        "webhook task should be marked succeeded when prune succeeds, got: {}",
         body["status"]
     );
 
     let logs = body["logs"].as_array().cloned().unwrap_or_default();
     let prune_log = logs
         .iter()
-        .find(|entry| entry.get("action") == Some(&Value::from("image-prune")))
+        .find(|entry| {
+            entry.get("action") == Some(&Value::from("image-prune"))
+                && entry.get("status") == Some(&Value::from("succeeded"))
+        })
         .cloned()
-        .expect("expected image-prune log entry for webhook prune failure");
-    assert_eq!(
-        prune_log.get("status"),
-        Some(&Value::from("failed")),
-        "image-prune log status should be 'failed' when prune command fails"
-    );
-    assert_eq!(
-        prune_log.get("level"),
-        Some(&Value::from("warning")),
-        "image-prune log level should be 'warning' when prune command fails"
-    );
+        .expect("expected image-prune log entry with status=succeeded for webhook prune success");
 
     let meta = prune_log.get("meta").cloned().unwrap_or_else(|| json!({}));
-    let exit = meta.get("exit").and_then(|v| v.as_str()).unwrap_or("");
-    assert!(
-        !exit.is_empty(),
-        "image-prune meta.exit should be a non-empty string when prune command fails"
+    assert_eq!(
+        meta.get("type").and_then(|v| v.as_str()),
+        Some("command"),
+        "image-prune meta.type should be 'command'"
     );
-    let stderr = meta.get("stderr").and_then(|v| v.as_str()).unwrap_or("");
+    let command = meta.get("command").and_then(|v| v.as_str()).unwrap_or("");
     assert!(
-        stderr.contains("simulated podman prune failure"),
-        "image-prune meta.stderr should include mock prune failure message, got: {stderr}"
+        command.contains("podman image prune -f"),
+        "image-prune meta.command should contain 'podman image prune -f', got: {command}"
     );
 
     Ok(())
 }
 
-async fn scenario_github_dispatch_failure() -> AnyResult<()> {
+async fn scenario_webhook_image_prune_failure() -> AnyResult<()> {
     let env = TestEnv::new()?;
     env.clear_mock_log()?;
 
-    // Choose a delivery id that produces a predictable transient unit name so
-    // we can force systemd-run failure via the mock.
-    let delivery_id = "dispatch-fail-1";
     let payload = github_registry_payload("koha", "svc-alpha", "main");
     let signature = env.github_signature(&payload);
     let request = HttpRequest::post("/github-package-update/svc-alpha")
         .header("x-github-event", "registry_package")
-        .header("x-github-delivery", delivery_id)
+        .header("x-github-delivery", "prune-fail")
         .header("x-hub-signature-256", &signature)
         .body(payload.clone());
 
-    // Transient unit name is webhook-task-<sanitize(delivery_id)>.
-    let mock_unit = "webhook-task-dispatch-fail-1";
     let response = env.send_request_with_env(request, |cmd| {
-        cmd.env("MOCK_SYSTEMD_RUN_FAIL", mock_unit);
+        configure_image_verify_mocks(cmd);
+        cmd.env("MOCK_PODMAN_PRUNE_FAIL", "1");
     })?;
     assert_eq!(
         response.status,
-        500,
-        "github dispatch failure should return 500 but got {} ({})",
-        response.status,
+        202,
+        "github webhook with failing prune should still be accepted: {}",
         response.body_text()
     );
 
     let pool = env.connect_db().await?;
     let events = env.fetch_events(&pool).await?;
-    let failure_event = events
+    let task_id = events
         .iter()
-        .find(|row| row.action == "github-webhook" && row.status == 500)
-        .cloned()
-        .expect("github-webhook dispatch failure event recorded");
-    let task_id = failure_event
-        .meta
-        .get("task_id")
+        .find(|row| row.action == "github-webhook")
+        .and_then(|row| row.meta.get("task_id"))
         .and_then(|v| v.as_str())
         .unwrap_or_default()
         .to_string();
     assert!(
         !task_id.is_empty(),
-        "github dispatch failure event must include task_id in meta"
+        "github-webhook events should include a task_id in meta for prune failure scenario"
+    );
+
+    let path = format!("/api/tasks/{task_id}");
+    let detail_resp = env.send_request(HttpRequest::get(&path))?;
+    assert_eq!(
+        detail_resp.status, 200,
+        "/api/tasks/:id should succeed for prune failure scenario"
+    );
+    let body = detail_resp.json_body()?;
+    assert_eq!(
+        body["status"],
+        Value::from("succeeded"),
+        "webhook task should remain succeeded even when prune fails, got: {}",
+        body["status"]
+    );
+
+    let logs = body["logs"].as_array().cloned().unwrap_or_default();
+    let prune_log = logs
+        .iter()
+        .find(|entry| entry.get("action") == Some(&Value::from("image-prune")))
+        .cloned()
+        .expect("expected image-prune log entry for webhook prune failure");
+    assert_eq!(
+        prune_log.get("status"),
+        Some(&Value::from("failed")),
+        "image-prune log status should be 'failed' when prune command fails"
+    );
+    assert_eq!(
+        prune_log.get("level"),
+        Some(&Value::from("warning")),
+        "image-prune log level should be 'warning' when prune command fails"
+    );
+
+    let meta = prune_log.get("meta").cloned().unwrap_or_else(|| json!({}));
+    let exit = meta.get("exit").and_then(|v| v.as_str()).unwrap_or("");
+    assert!(
+        !exit.is_empty(),
+        "image-prune meta.exit should be a non-empty string when prune command fails"
+    );
+    let stderr = meta
+        .get("stderr_tail")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    assert!(
+        stderr.contains("simulated podman prune failure"),
+        "image-prune meta.stderr should include mock prune failure message, got: {stderr}"
+    );
+
+    Ok(())
+}
+
+async fn scenario_github_dispatch_failure() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.clear_mock_log()?;
+
+    // Choose a delivery id that produces a predictable transient unit name so
+    // we can force systemd-run failure via the mock.
+    let delivery_id = "dispatch-fail-1";
+    let payload = github_registry_payload("koha", "svc-alpha", "main");
+    let signature = env.github_signature(&payload);
+    let request = HttpRequest::post("/github-package-update/svc-alpha")
+        .header("x-github-event", "registry_package")
+        .header("x-github-delivery", delivery_id)
+        .header("x-hub-signature-256", &signature)
+        .body(payload.clone());
+
+    // Transient unit name is webhook-task-<sanitize(delivery_id)>.
+    let mock_unit = "webhook-task-dispatch-fail-1";
+    let response = env.send_request_with_env(request, |cmd| {
+        cmd.env("MOCK_SYSTEMD_RUN_FAIL", mock_unit);
+    })?;
+    assert_eq!(
+        response.status,
+        500,
+        "github dispatch failure should return 500 but got {} ({})",
+        response.status,
+        response.body_text()
+    );
+
+    let pool = env.connect_db().await?;
+    let events = env.fetch_events(&pool).await?;
+    let failure_event = events
+        .iter()
+        .find(|row| row.action == "github-webhook" && row.status == 500)
+        .cloned()
+        .expect("github-webhook dispatch failure event recorded");
+    let task_id = failure_event
+        .meta
+        .get("task_id")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+    assert!(
+        !task_id.is_empty(),
+        "github dispatch failure event must include task_id in meta"
     );
     assert!(
         failure_event.meta.get("error").is_some(),
@@ -713,6 +2119,38 @@ async fn scenario_github_dispatch_failure() -> AnyResult<()> {
         "expected at least one task-dispatch-failed log entry with status=failed for github dispatch failure"
     );
 
+    // The export bundles the same detail with the events that triggered it.
+    let export = env.send_request(HttpRequest::get(&format!("/api/tasks/{task_id}/export")))?;
+    assert_eq!(export.status, 200, "task export: {}", export.body_text());
+    let disposition = export
+        .headers
+        .get("content-disposition")
+        .cloned()
+        .unwrap_or_default();
+    assert!(
+        disposition.starts_with("attachment;") && disposition.contains(&task_id),
+        "export should be an attachment named after the task: {disposition}"
+    );
+    let bundle = export.json_body()?;
+    assert_eq!(bundle["task"]["task_id"], Value::from(task_id.as_str()));
+    assert!(
+        !bundle["task"]["logs"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+            .is_empty()
+    );
+    let exported_events = bundle["events"].as_array().cloned().unwrap_or_default();
+    assert!(
+        exported_events
+            .iter()
+            .any(|event| event["action"] == "github-webhook" && event["status"] == 500),
+        "export should include the triggering webhook event: {bundle}"
+    );
+
+    let missing = env.send_request(HttpRequest::get("/api/tasks/tsk_missing/export"))?;
+    assert_eq!(missing.status, 404);
+
     Ok(())
 }
 
@@ -736,6 +2174,19 @@ async fn scenario_rate_limit_and_prune() -> AnyResult<()> {
         env.send_request(HttpRequest::post("/auto-update").header("x-podup-csrf", "1"))?;
     assert_eq!(rate_limited.status, 429);
 
+    let limits = env.send_request(HttpRequest::get("/api/rate-limits"))?;
+    assert_eq!(limits.status, 200, "{}", limits.body_text());
+    let limits = limits.json_body()?;
+    let manual = limits["limiters"]
+        .as_array()
+        .and_then(|list| list.iter().find(|item| item["scope"] == "manual"))
+        .cloned()
+        .expect("manual limiter listed");
+    assert_eq!(manual["bucket"], "manual-auto-update");
+    assert_eq!(manual["exceeded"], true);
+    assert_eq!(manual["windows"][0]["count"], 3);
+    assert!(manual["windows"][0]["reset_in_secs"].as_u64().unwrap_or(0) > 0);
+
     sqlx::query("UPDATE rate_limit_tokens SET ts = ts - 200000")
         .execute(&pool)
         .await?;
@@ -786,6 +2237,132 @@ async fn scenario_rate_limit_and_prune() -> AnyResult<()> {
     Ok(())
 }
 
+async fn scenario_tasks_stats() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let pool = env.connect_db().await?;
+    // Drop tasks left behind by DB initialization so the counts are exact.
+    sqlx::query("DELETE FROM tasks").execute(&pool).await?;
+    let now = current_unix_secs() as i64;
+    // (task_id, kind, status, age secs, duration secs)
+    let seeds: [(&str, &str, &str, i64, Option<i64>); 6] = [
+        ("stats-1", "manual", "succeeded", 60, Some(10)),
+        ("stats-2", "manual", "succeeded", 120, Some(20)),
+        ("stats-3", "manual", "failed", 180, Some(30)),
+        ("stats-4", "github-webhook", "succeeded", 240, Some(100)),
+        ("stats-5", "github-webhook", "running", 300, None),
+        // Outside the default 24h window.
+        ("stats-old", "manual", "failed", 3 * 86_400, Some(5)),
+    ];
+    for (task_id, kind, status, age, duration) in seeds {
+        let created = now - age;
+        sqlx::query(
+            "INSERT INTO tasks (task_id, kind, status, created_at, started_at, finished_at, summary, meta, trigger_source) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(task_id)
+        .bind(kind)
+        .bind(status)
+        .bind(created)
+        .bind(created)
+        .bind(duration.map(|secs| created + secs))
+        .bind("stats task")
+        .bind("{}")
+        .bind("test")
+        .execute(&pool)
+        .await?;
+    }
+
+    let response = env.send_request(HttpRequest::get("/api/tasks/stats"))?;
+    assert_eq!(response.status, 200, "{}", response.body_text());
+    let json = response.json_body()?;
+    assert_eq!(json["window_hours"], 24);
+    assert_eq!(json["total"], 5);
+    assert_eq!(json["by_status"]["succeeded"], 3);
+    assert_eq!(json["by_status"]["failed"], 1);
+    assert_eq!(json["by_status"]["running"], 1);
+    assert_eq!(json["by_kind"]["manual"]["total"], 3);
+    assert_eq!(json["by_kind"]["github-webhook"]["by_status"]["running"], 1);
+    assert_eq!(json["success_rate"], 0.75);
+    assert_eq!(json["durations"]["completed"], 4);
+    assert_eq!(json["durations"]["avg_secs"], 40.0);
+    assert_eq!(json["durations"]["p95_secs"], 100);
+
+    let wide = env.send_request(HttpRequest::get("/api/tasks/stats?window_hours=96"))?;
+    assert_eq!(wide.status, 200);
+    let json = wide.json_body()?;
+    assert_eq!(json["total"], 6);
+    assert_eq!(json["by_status"]["failed"], 2);
+
+    let invalid = env.send_request(HttpRequest::get("/api/tasks/stats?window_hours=0"))?;
+    assert_eq!(invalid.status, 400);
+
+    Ok(())
+}
+
+async fn scenario_task_log_max_entries() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+    let pool = env.connect_db().await?;
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    sqlx::query(
+        "INSERT INTO tasks (task_id, kind, status, created_at, started_at, finished_at, summary, meta, trigger_source) \
+         VALUES ('chatty-task', 'manual', 'succeeded', ?, ?, ?, 'chatty', '{}', 'test')",
+    )
+    .bind(now)
+    .bind(now)
+    .bind(now)
+    .execute(&pool)
+    .await?;
+    for idx in 0..5 {
+        sqlx::query(
+            "INSERT INTO task_logs (task_id, ts, level, action, status, summary, unit, meta) \
+             VALUES ('chatty-task', ?, 'info', 'chatter', 'succeeded', ?, NULL, '{}')",
+        )
+        .bind(now)
+        .bind(format!("line {idx}"))
+        .execute(&pool)
+        .await?;
+    }
+
+    let prune = |dry_run: bool| -> AnyResult<Value> {
+        let mut cmd = env.command();
+        cmd.arg("prune-state").arg("--json");
+        if dry_run {
+            cmd.arg("--dry-run");
+        }
+        cmd.env("PODUP_TASK_LOG_MAX_ENTRIES", "2");
+        let output = env.run_command(cmd)?;
+        assert!(output.status.success(), "prune failed: {}", output.stderr);
+        Ok(serde_json::from_str(output.stdout.trim())?)
+    };
+    let chatty_logs = || async {
+        sqlx::query_scalar::<_, String>(
+            "SELECT summary FROM task_logs WHERE task_id = 'chatty-task' ORDER BY id",
+        )
+        .fetch_all(&pool)
+        .await
+    };
+
+    let preview = prune(true)?;
+    assert_eq!(preview["task_log_max_entries"], 2);
+    assert!(preview["task_logs_trimmed"].as_u64().unwrap_or(0) >= 3);
+    assert_eq!(chatty_logs().await?.len(), 5);
+
+    let report = prune(false)?;
+    assert!(report["task_logs_trimmed"].as_u64().unwrap_or(0) >= 3);
+    assert_eq!(chatty_logs().await?, vec!["line 3", "line 4"]);
+    let task_rows: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM tasks WHERE task_id = 'chatty-task'")
+            .fetch_one(&pool)
+            .await?;
+    assert_eq!(task_rows, 1, "task summary record persists");
+
+    Ok(())
+}
+
 async fn scenario_task_prune_retention() -> AnyResult<()> {
     let env = TestEnv::new()?;
     env.ensure_db_initialized().await?;
@@ -1080,12 +2657,1755 @@ async fn scenario_manual_api() -> AnyResult<()> {
 
     let pool = env.connect_db().await?;
     let events = env.fetch_events(&pool).await?;
-    assert!(events.iter().any(|row| row.action == "manual-trigger"));
-    assert!(
-        events
-            .iter()
-            .any(|row| row.action == "manual-service-upgrade")
-    );
+    assert!(events.iter().any(|row| row.action == "manual-trigger"));
+    assert!(
+        events
+            .iter()
+            .any(|row| row.action == "manual-service-upgrade")
+    );
+
+    Ok(())
+}
+
+async fn scenario_pull_digest_mismatch() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    // The registry now advertises a digest the pulled image does not carry.
+    let registry_mock = json!({ "ghcr.io/koha/svc-alpha:main": "sha256:cccccccc" });
+    let run_service = |policy: &str| -> AnyResult<Value> {
+        env.clear_mock_log()?;
+        let response = env.send_request_with_env(
+            HttpRequest::post("/api/manual/services/svc-alpha")
+                .header("content-type", "application/json")
+                .header("x-podup-csrf", "1")
+                .body(
+                    json!({ "image": "ghcr.io/koha/svc-alpha:main", "reason": "digest" })
+                        .to_string()
+                        .into_bytes(),
+                ),
+            |cmd| {
+                configure_image_verify_mocks(cmd);
+                cmd.env("PODUP_REGISTRY_DIGEST_MOCK", registry_mock.to_string());
+                cmd.env("PODUP_PULL_DIGEST_POLICY", policy);
+            },
+        )?;
+        assert_eq!(response.status, 202, "{}", response.body_text());
+        let task_id = response.json_body()?["task_id"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        env.send_request(HttpRequest::get(&format!("/api/tasks/{task_id}")))?
+            .json_body()
+    };
+    let find_log = |detail: &Value, action: &str| -> Option<Value> {
+        detail["logs"]
+            .as_array()
+            .and_then(|logs| logs.iter().find(|l| l["action"] == action).cloned())
+    };
+
+    let failed = run_service("fail")?;
+    assert_eq!(failed["status"], "failed", "{failed}");
+    let mismatch = find_log(&failed, "digest-mismatch").expect("digest-mismatch log");
+    assert_eq!(mismatch["level"], "error");
+    assert_eq!(mismatch["meta"]["pulled_digests"][0], "sha256:bbbbbbbb");
+    assert_eq!(mismatch["meta"]["remote_digests"][0], "sha256:cccccccc");
+    assert!(find_log(&failed, "restart-unit").is_none());
+    assert!(
+        env.read_mock_log()?
+            .iter()
+            .all(|line| !line.contains("restart svc-alpha.service")),
+        "unit must not restart onto a mismatched image"
+    );
+
+    let warned = run_service("warn")?;
+    let mismatch = find_log(&warned, "digest-mismatch").expect("digest-mismatch log");
+    assert_eq!(mismatch["level"], "warning");
+    assert!(find_log(&warned, "restart-unit").is_some());
+
+    let off = run_service("off")?;
+    assert!(find_log(&off, "digest-mismatch").is_none());
+    assert!(find_log(&off, "pull-digest-check").is_none());
+
+    Ok(())
+}
+
+async fn scenario_github_webhook_skip_annotation() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+    env.clear_mock_log()?;
+
+    let labels = json!({
+        "ghcr.io/koha/svc-alpha:main": {
+            "io.podup.skip": "true",
+            "org.opencontainers.image.revision": "abc123",
+        }
+    });
+    let payload = github_registry_payload("koha", "svc-alpha", "main");
+    let response = env.send_request_with_env(
+        HttpRequest::post("/github-package-update/svc-alpha")
+            .header("x-github-event", "registry_package")
+            .header("x-github-delivery", "delivery-skip-annotation")
+            .header("x-hub-signature-256", &env.github_signature(&payload))
+            .body(payload),
+        |cmd| {
+            configure_image_verify_mocks(cmd);
+            cmd.env("PODUP_SKIP_ANNOTATION", "io.podup.skip=true");
+            cmd.env("PODUP_REGISTRY_LABELS_MOCK", labels.to_string());
+        },
+    )?;
+    assert_eq!(response.status, 202, "{}", response.body_text());
+    assert!(
+        env.read_mock_log()?
+            .iter()
+            .all(|line| !line.contains("podman pull ghcr.io/koha/svc-alpha:main")),
+        "annotated image must not be pulled"
+    );
+
+    let pool = env.connect_db().await?;
+    let task_id: String = sqlx::query_scalar(
+        "SELECT task_id FROM tasks WHERE kind = 'github-webhook' ORDER BY created_at DESC LIMIT 1",
+    )
+    .fetch_one(&pool)
+    .await?;
+    let detail = env
+        .send_request(HttpRequest::get(&format!("/api/tasks/{task_id}")))?
+        .json_body()?;
+    assert_eq!(detail["status"], "skipped", "{detail}");
+    let skip_log = detail["logs"]
+        .as_array()
+        .and_then(|logs| {
+            logs.iter()
+                .find(|l| l["action"] == "image-annotation-skip")
+                .cloned()
+        })
+        .expect("image-annotation-skip log");
+    assert_eq!(skip_log["meta"]["reason"], "annotation");
+    assert_eq!(skip_log["meta"]["annotation"], "io.podup.skip");
+
+    Ok(())
+}
+
+async fn scenario_github_webhook_unit_lock() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+    env.clear_mock_log()?;
+
+    // A deploy for svc-alpha is already in flight and holds the unit lock.
+    let pool = env.connect_db().await?;
+    let now = current_unix_secs() as i64;
+    sqlx::query(
+        "INSERT INTO tasks (task_id, kind, status, created_at, started_at, summary, meta, trigger_source) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind("inflight-alpha")
+    .bind("github-webhook")
+    .bind("running")
+    .bind(now)
+    .bind(now)
+    .bind("in-flight deploy")
+    .bind("{}")
+    .bind("github")
+    .execute(&pool)
+    .await?;
+    sqlx::query("INSERT INTO task_units (task_id, unit, status) VALUES (?, ?, ?)")
+        .bind("inflight-alpha")
+        .bind("svc-alpha.service")
+        .bind("running")
+        .execute(&pool)
+        .await?;
+    sqlx::query("INSERT INTO image_locks (bucket, acquired_at) VALUES (?, ?)")
+        .bind("unit:svc-alpha.service")
+        .bind(now)
+        .execute(&pool)
+        .await?;
+
+    let send = |delivery: &str| -> AnyResult<HttpResponse> {
+        let payload = github_registry_payload("koha", "svc-alpha", "main");
+        env.send_request_with_env(
+            HttpRequest::post("/github-package-update/svc-alpha")
+                .header("x-github-event", "registry_package")
+                .header("x-github-delivery", delivery)
+                .header("x-hub-signature-256", &env.github_signature(&payload))
+                .body(payload),
+            |cmd| {
+                configure_image_verify_mocks(cmd);
+            },
+        )
+    };
+
+    let coalesced = send("delivery-coalesce-1")?;
+    assert_eq!(coalesced.status, 202, "{}", coalesced.body_text());
+    assert!(
+        env.read_mock_log()?
+            .iter()
+            .all(|line| !line.contains("podman pull")),
+        "a coalesced trigger must not start a second deploy"
+    );
+    let coalesced_logs: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM task_logs WHERE task_id = 'inflight-alpha' AND action = 'coalesced'",
+    )
+    .fetch_one(&pool)
+    .await?;
+    assert_eq!(coalesced_logs, 1);
+    let tasks: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tasks WHERE kind = 'github-webhook'")
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(tasks, 1);
+
+    // Without a task the lock may belong to a delivery that has not created
+    // its task yet, so a fresh lock is never taken over.
+    sqlx::query("UPDATE tasks SET status = 'failed' WHERE task_id = 'inflight-alpha'")
+        .execute(&pool)
+        .await?;
+    let busy = send("delivery-coalesce-2")?;
+    assert_eq!(busy.status, 429, "{}", busy.body_text());
+    assert!(
+        env.read_mock_log()?
+            .iter()
+            .all(|line| !line.contains("podman pull")),
+        "a fresh lock must not be taken over"
+    );
+
+    // A lock older than the task timeout is taken over and released when the
+    // new deploy finishes.
+    sqlx::query("UPDATE image_locks SET acquired_at = ? WHERE bucket = ?")
+        .bind(now - 3 * 3600)
+        .bind("unit:svc-alpha.service")
+        .execute(&pool)
+        .await?;
+    let deployed = send("delivery-coalesce-3")?;
+    assert_eq!(deployed.status, 202, "{}", deployed.body_text());
+    assert!(
+        env.read_mock_log()?
+            .iter()
+            .any(|line| line.contains("podman pull ghcr.io/koha/svc-alpha:main")),
+        "deploy runs after the orphaned lock is taken over"
+    );
+    let locks: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM image_locks WHERE bucket LIKE 'unit:%'")
+            .fetch_one(&pool)
+            .await?;
+    assert_eq!(locks, 0);
+
+    Ok(())
+}
+
+async fn scenario_image_locks_filter_and_release() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let pool = env.connect_db().await?;
+    let now = current_unix_secs() as i64;
+    let meta = json!({
+        "type": "github-webhook",
+        "unit": "svc-alpha.service",
+        "image": "ghcr.io/koha/svc-alpha:main",
+        "event": "registry_package",
+        "delivery": "locks-delivery",
+        "path": "/github-package-update/svc-alpha",
+    });
+    sqlx::query(
+        "INSERT INTO tasks (task_id, kind, status, created_at, started_at, summary, meta, trigger_source) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind("locks-running")
+    .bind("github-webhook")
+    .bind("running")
+    .bind(now)
+    .bind(now)
+    .bind("in-flight deploy")
+    .bind(meta.to_string())
+    .bind("github")
+    .execute(&pool)
+    .await?;
+    for (bucket, acquired_at) in [
+        ("unit:svc-alpha.service", now),
+        ("ghcr.io_koha_svc-alpha_main", now),
+        ("ghcr.io_koha_svc-beta_main", now - 3600),
+    ] {
+        sqlx::query("INSERT INTO image_locks (bucket, acquired_at) VALUES (?, ?)")
+            .bind(bucket)
+            .bind(acquired_at)
+            .execute(&pool)
+            .await?;
+    }
+
+    let buckets = |query: &str| -> AnyResult<Vec<String>> {
+        let response = env.send_request(HttpRequest::get(&format!("/api/image-locks{query}")))?;
+        assert_eq!(response.status, 200, "{}", response.body_text());
+        Ok(response.json_body()?["locks"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|lock| lock["bucket"].as_str().map(str::to_string))
+            .collect())
+    };
+    assert_eq!(buckets("")?.len(), 3);
+    let mut alpha = buckets("?unit=svc-alpha")?;
+    alpha.sort();
+    assert_eq!(
+        alpha,
+        vec!["ghcr.io_koha_svc-alpha_main", "unit:svc-alpha.service"]
+    );
+    assert_eq!(
+        buckets("?image=ghcr.io/koha/svc-beta:main")?,
+        vec!["ghcr.io_koha_svc-beta_main"]
+    );
+    assert_eq!(
+        buckets("?min_age_secs=600")?,
+        vec!["ghcr.io_koha_svc-beta_main"]
+    );
+
+    let release = |bucket: &str| -> AnyResult<HttpResponse> {
+        env.send_request(
+            HttpRequest::new("DELETE", &format!("/api/image-locks/{bucket}"))
+                .header("x-podup-csrf", "1"),
+        )
+    };
+    let held = release("unit:svc-alpha.service")?;
+    assert_eq!(held.status, 409, "{}", held.body_text());
+    let body = held.json_body()?;
+    assert_eq!(body["error"]["code"], "conflict");
+    assert_eq!(body["error"]["details"]["task_id"], "locks-running");
+
+    let stale = release("ghcr.io_koha_svc-beta_main")?;
+    assert_eq!(stale.status, 200, "{}", stale.body_text());
+    assert_eq!(stale.json_body()?["forced"], false);
+
+    let forced = release("unit:svc-alpha.service?force=1")?;
+    assert_eq!(forced.status, 200, "{}", forced.body_text());
+    let body = forced.json_body()?;
+    assert_eq!(body["forced"], true);
+    assert_eq!(body["held_by_task"], "locks-running");
+
+    let events = env.fetch_events(&pool).await?;
+    assert!(
+        events.iter().any(|event| event.action == "image-locks-api"
+            && event.status == 200
+            && event.meta["forced"] == true),
+        "forced release is recorded"
+    );
+    assert_eq!(buckets("")?, vec!["ghcr.io_koha_svc-alpha_main"]);
+
+    Ok(())
+}
+
+async fn scenario_systemd_run_properties() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+    env.clear_mock_log()?;
+
+    let payload = github_registry_payload("koha", "svc-alpha", "main");
+    let response = env.send_request_with_env(
+        HttpRequest::post("/github-package-update/svc-alpha")
+            .header("x-github-event", "registry_package")
+            .header("x-github-delivery", "delivery-properties")
+            .header("x-hub-signature-256", &env.github_signature(&payload))
+            .body(payload),
+        |cmd| {
+            configure_image_verify_mocks(cmd);
+            cmd.env(
+                "PODUP_SYSTEMD_RUN_PROPERTIES",
+                "MemoryMax=512M,CPUQuota=50% ExecStartPre=/bin/false",
+            );
+        },
+    )?;
+    assert_eq!(response.status, 202, "{}", response.body_text());
+
+    let dispatch = env
+        .read_mock_log()?
+        .into_iter()
+        .find(|line| line.contains("--unit=webhook-task-delivery-properties"))
+        .expect("systemd-run dispatch recorded");
+    assert!(
+        dispatch.contains("--property=MemoryMax=512M --property=CPUQuota=50%"),
+        "{dispatch}"
+    );
+    assert!(!dispatch.contains("ExecStartPre"), "{dispatch}");
+
+    let pool = env.connect_db().await?;
+    let meta: String = sqlx::query_scalar(
+        "SELECT meta FROM task_logs WHERE action = 'systemd-run-properties' LIMIT 1",
+    )
+    .fetch_one(&pool)
+    .await?;
+    let meta: Value = serde_json::from_str(&meta)?;
+    assert_eq!(
+        meta["properties"],
+        json!(["MemoryMax=512M", "CPUQuota=50%"])
+    );
+
+    // The properties also live on the task itself, so detail and export show
+    // them without digging through the log.
+    let task_id: String = sqlx::query_scalar(
+        "SELECT task_id FROM task_logs WHERE action = 'systemd-run-properties' LIMIT 1",
+    )
+    .fetch_one(&pool)
+    .await?;
+    let task_meta: String = sqlx::query_scalar("SELECT meta FROM tasks WHERE task_id = ?")
+        .bind(&task_id)
+        .fetch_one(&pool)
+        .await?;
+    let task_meta: Value = serde_json::from_str(&task_meta)?;
+    assert_eq!(
+        task_meta["unit_properties"],
+        json!(["MemoryMax=512M", "CPUQuota=50%"])
+    );
+    assert_eq!(task_meta["type"], "github-webhook");
+    for path in [
+        format!("/api/tasks/{task_id}"),
+        format!("/api/tasks/{task_id}/export"),
+    ] {
+        let response = env.send_request(HttpRequest::get(&path))?;
+        assert_eq!(response.status, 200, "{path}: {}", response.body_text());
+        let body = response.json_body()?;
+        let detail = body.get("task").unwrap_or(&body);
+        assert_eq!(
+            detail["unit_properties"],
+            json!(["MemoryMax=512M", "CPUQuota=50%"]),
+            "{path}"
+        );
+    }
+
+    Ok(())
+}
+
+async fn scenario_github_webhook_default_tag() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+    env.clear_mock_log()?;
+
+    let mut payload: Value =
+        serde_json::from_slice(&github_registry_payload("koha", "svc-alpha", "main"))?;
+    payload["registry_package"]["package_version"]["metadata"]["container"]["tags"] = json!([]);
+    let payload = payload.to_string().into_bytes();
+    let send = |delivery: &str, configure: &dyn Fn(&mut Command)| -> AnyResult<HttpResponse> {
+        env.send_request_with_env(
+            HttpRequest::post("/github-package-update/svc-alpha")
+                .header("x-github-event", "registry_package")
+                .header("x-github-delivery", delivery)
+                .header("x-hub-signature-256", &env.github_signature(&payload))
+                .body(payload.clone()),
+            |cmd| {
+                configure_image_verify_mocks(cmd);
+                configure(cmd);
+            },
+        )
+    };
+
+    let ignored = send("delivery-no-tag", &|_| {})?;
+    assert_eq!(ignored.status, 202);
+    assert_eq!(ignored.body_text(), "event ignored");
+    assert!(
+        env.read_mock_log()?
+            .iter()
+            .all(|line| !line.contains("podman pull")),
+        "payload without a tag is ignored by default"
+    );
+
+    let defaulted = send("delivery-default-tag", &|cmd| {
+        cmd.env("PODUP_DEFAULT_TAG", "latest");
+        cmd.env("PODUP_UNIT_DEFAULT_TAG", "svc-alpha=main");
+    })?;
+    assert_eq!(defaulted.status, 202, "{}", defaulted.body_text());
+    assert!(
+        env.read_mock_log()?
+            .iter()
+            .any(|line| line.contains("podman pull ghcr.io/koha/svc-alpha:main")),
+        "the per-unit default tag wins over PODUP_DEFAULT_TAG"
+    );
+
+    Ok(())
+}
+
+async fn scenario_units_discovered_verbose() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let container_dir = env.state_dir.join("containers/systemd");
+    fs::create_dir_all(&container_dir)?;
+    fs::write(
+        container_dir.join("svc-gamma.container"),
+        b"[Container]\nImage=example\nAutoupdate=registry",
+    )?;
+    fs::write(
+        container_dir.join("svc-eps.container"),
+        b"[Container]\nImage=ghcr.io/koha/svc-eps:main\nAutoupdate=registry",
+    )?;
+    fs::write(
+        container_dir.join("svc-delta.service"),
+        b"[Unit]\nDescription=dummy",
+    )?;
+
+    let fetch = |query: &str| -> AnyResult<Value> {
+        let response = env.send_request_with_env(
+            HttpRequest::get(&format!("/api/units/discovered{query}")),
+            |cmd| {
+                cmd.env("PODUP_CONTAINER_DIR", &container_dir);
+            },
+        )?;
+        assert_eq!(response.status, 200, "{}", response.body_text());
+        response.json_body()
+    };
+    let unit_entry = |body: &Value, unit: &str| -> Value {
+        body["units"]
+            .as_array()
+            .and_then(|units| units.iter().find(|u| u["unit"] == unit).cloned())
+            .unwrap_or_else(|| panic!("{unit} listed: {body}"))
+    };
+
+    let plain = fetch("")?;
+    assert_eq!(plain["verbose"], false);
+    let gamma = unit_entry(&plain, "svc-gamma.service");
+    assert_eq!(gamma["source"], "dir");
+    assert!(gamma.get("raw_image").is_none());
+
+    let verbose = fetch("?verbose=1")?;
+    let gamma = unit_entry(&verbose, "svc-gamma.service");
+    assert_eq!(gamma["raw_image"], "example");
+    assert_eq!(gamma["image_source"], "unit-file");
+    assert_eq!(gamma["error"], "invalid-image");
+    assert_eq!(gamma["deploy_eligible"], false);
+
+    let eps = unit_entry(&verbose, "svc-eps.service");
+    assert_eq!(eps["image"], "ghcr.io/koha/svc-eps:main");
+    assert_eq!(eps["deploy_eligible"], true);
+    assert_eq!(eps["error"], Value::Null);
+
+    let delta = unit_entry(&verbose, "svc-delta.service");
+    assert_eq!(delta["raw_image"], Value::Null);
+    assert_eq!(delta["deploy_eligible"], false);
+    assert!(
+        delta["error"].as_str().is_some_and(|err| !err.is_empty()),
+        "{delta}"
+    );
+
+    Ok(())
+}
+
+async fn scenario_discovery_degrades_without_container_dir() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let ps_json = json!([
+        {
+            "Id": "cid-ps",
+            "State": "running",
+            "Labels": {
+                "io.containers.autoupdate": "registry",
+                "PODMAN_SYSTEMD_UNIT": "svc-ps.service"
+            }
+        }
+    ]);
+    let response = env.send_request_with_env(HttpRequest::get("/api/manual/services"), |cmd| {
+        // Not an absolute path, so the dir source cannot be used at all.
+        cmd.env("PODUP_CONTAINER_DIR", "containers/systemd");
+        cmd.env("MOCK_PODMAN_PS_JSON", ps_json.to_string());
+    })?;
+    assert_eq!(response.status, 200, "{}", response.body_text());
+    let body = response.json_body()?;
+    assert!(
+        body["discovered"]["units"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|unit| unit == "svc-ps.service"),
+        "ps discovery still reported: {body}"
+    );
+    let warnings = body["discovered"]["warnings"].as_array().unwrap();
+    assert_eq!(warnings.len(), 1, "{body}");
+    assert_eq!(warnings[0]["source"], "dir");
+    assert!(warnings[0]["error"].as_str().is_some_and(|e| !e.is_empty()));
+
+    Ok(())
+}
+
+async fn scenario_db_migrations_status() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let response = env.send_request(HttpRequest::get("/api/db/migrations"))?;
+    assert_eq!(response.status, 200, "{}", response.body_text());
+    let body = response.json_body()?;
+    assert_eq!(body["status"], "ok");
+    assert_eq!(body["needs_attention"], false);
+    assert_eq!(body["pending"].as_array().map(|p| p.len()), Some(0));
+    assert_eq!(body["latest_applied"], body["latest_available"]);
+    let first_version = body["applied"][0]["version"]
+        .as_i64()
+        .expect("applied migrations listed");
+    assert_eq!(body["applied"][0]["checksum_ok"], true);
+
+    let pool = env.connect_db().await?;
+    sqlx::query("UPDATE _sqlx_migrations SET checksum = x'00' WHERE version = ?")
+        .bind(first_version)
+        .execute(&pool)
+        .await?;
+    pool.close().await;
+
+    let response = env.send_request(HttpRequest::get("/api/db/migrations"))?;
+    assert_eq!(response.status, 200, "{}", response.body_text());
+    let body = response.json_body()?;
+    assert_eq!(body["status"], "needs-attention");
+    assert_eq!(body["needs_attention"], true);
+    assert_eq!(body["checksum_mismatch"], json!([first_version]));
+    assert_eq!(body["applied"][0]["checksum_ok"], false);
+    assert!(body["db_error"].is_string(), "{body}");
+
+    let response = env.send_request(HttpRequest::post("/api/db/migrations"))?;
+    assert_eq!(response.status, 405);
+
+    Ok(())
+}
+
+async fn scenario_manual_restart_action() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let post = |path: &str, body: Value| -> AnyResult<HttpResponse> {
+        env.send_request_with_env(
+            HttpRequest::post(path)
+                .header("content-type", "application/json")
+                .header("x-podup-csrf", "1")
+                .body(body.to_string().into_bytes()),
+            |cmd| {
+                cmd.env("PODUP_UNIT_RESTART_ACTION", "svc-beta=reload");
+            },
+        )
+    };
+
+    let invalid = post(
+        "/api/manual/trigger",
+        json!({ "units": ["svc-alpha.service"], "action": "kill" }),
+    )?;
+    assert_eq!(invalid.status, 400, "{}", invalid.body_text());
+
+    env.clear_mock_log()?;
+    let overridden = post(
+        "/api/manual/trigger",
+        json!({ "units": ["svc-alpha.service", "svc-beta.service"], "action": "try-restart" }),
+    )?;
+    assert_eq!(overridden.status, 202, "{}", overridden.body_text());
+    let log_lines = env.read_mock_log()?;
+    assert!(
+        log_lines
+            .iter()
+            .any(|line| line.contains("systemctl --user try-restart svc-alpha.service")),
+        "per-call action applied: {log_lines:?}"
+    );
+    assert!(
+        log_lines
+            .iter()
+            .any(|line| line.contains("systemctl --user try-restart svc-beta.service")),
+        "per-call action wins over unit preference: {log_lines:?}"
+    );
+
+    env.clear_mock_log()?;
+    let preferred = post("/api/manual/services/svc-beta", json!({}))?;
+    assert_eq!(preferred.status, 202, "{}", preferred.body_text());
+    let log_lines = env.read_mock_log()?;
+    assert!(
+        log_lines
+            .iter()
+            .any(|line| line.contains("systemctl --user reload svc-beta.service")),
+        "unit preference applied: {log_lines:?}"
+    );
+    assert!(
+        !log_lines
+            .iter()
+            .any(|line| line.contains("systemctl --user restart svc-beta.service")),
+        "no restart issued: {log_lines:?}"
+    );
+
+    env.clear_mock_log()?;
+    let default = post("/api/manual/services/svc-alpha", json!({}))?;
+    assert_eq!(default.status, 202, "{}", default.body_text());
+    let log_lines = env.read_mock_log()?;
+    assert!(
+        log_lines
+            .iter()
+            .any(|line| line.contains("systemctl --user restart svc-alpha.service")),
+        "restart stays the default: {log_lines:?}"
+    );
+
+    Ok(())
+}
+
+async fn scenario_prod_requires_forward_auth() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let mut cmd = env.command();
+    cmd.arg("http-server")
+        .env("PODUP_ENV", "prod")
+        .env_remove("PODUP_DEV_OPEN_ADMIN")
+        .env_remove("PODUP_FWD_AUTH_HEADER")
+        .env_remove("PODUP_FWD_AUTH_ADMIN_VALUE")
+        .env("PODUP_REQUIRE_AUTH", "1");
+    let result = env.run_command(cmd)?;
+    assert!(!result.status.success());
+    assert!(
+        result.stderr.contains("PODUP_FWD_AUTH_HEADER")
+            && result.stderr.contains("PODUP_REQUIRE_AUTH"),
+        "startup error should name the gaps: {}",
+        result.stderr
+    );
+
+    let pool = env.connect_db().await?;
+    let events = env.fetch_events(&pool).await?;
+    let event = events
+        .iter()
+        .find(|e| e.action == "auth-misconfigured")
+        .expect("auth-misconfigured event recorded");
+    assert_eq!(event.status, 500);
+    assert_eq!(event.meta["require_auth"], true);
+    assert_eq!(
+        event.meta["missing"],
+        json!(["PODUP_FWD_AUTH_HEADER", "PODUP_FWD_AUTH_ADMIN_VALUE"])
+    );
+
+    Ok(())
+}
+
+async fn scenario_manual_deploy_image() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let container_dir = env.state_dir.join("containers/systemd");
+    fs::create_dir_all(&container_dir)?;
+    fs::write(
+        container_dir.join("svc-alpha.container"),
+        b"[Container]\nImage=ghcr.io/koha/shared:stable\n",
+    )?;
+    fs::write(
+        container_dir.join("svc-beta.container"),
+        b"[Container]\nImage=ghcr.io/koha/other:stable\n",
+    )?;
+
+    let post = |body: Value| -> AnyResult<HttpResponse> {
+        env.send_request_with_env(
+            HttpRequest::post("/api/manual/deploy-image")
+                .header("content-type", "application/json")
+                .header("x-podup-csrf", "1")
+                .body(body.to_string().into_bytes()),
+            |cmd| {
+                cmd.env("PODUP_CONTAINER_DIR", &container_dir);
+                configure_image_verify_mocks(cmd);
+            },
+        )
+    };
+
+    let missing = post(json!({ "image": "ghcr.io/koha/unknown:main" }))?;
+    assert_eq!(missing.status, 404, "{}", missing.body_text());
+
+    let planned = post(json!({ "image": "ghcr.io/koha/shared:main", "dry_run": true }))?;
+    assert_eq!(planned.status, 202, "{}", planned.body_text());
+    let body = planned.json_body()?;
+    assert_eq!(body["matched"], json!(["svc-alpha.service"]));
+    assert_eq!(body["deploying"][0]["image"], "ghcr.io/koha/shared:stable");
+    assert_eq!(body["deploying"][0]["status"], "dry-run");
+
+    let deployed = post(json!({ "image": "ghcr.io/koha/shared@sha256:abc" }))?;
+    assert_eq!(deployed.status, 202, "{}", deployed.body_text());
+    let body = deployed.json_body()?;
+    let task_id = body["deploying"][0]["task_id"]
+        .as_str()
+        .expect("task scheduled for the matched unit")
+        .to_string();
+
+    let pool = env.connect_db().await?;
+    let row = sqlx::query("SELECT trigger_path FROM tasks WHERE task_id = ?")
+        .bind(&task_id)
+        .fetch_one(&pool)
+        .await?;
+    let trigger_path: Option<String> = row.get("trigger_path");
+    assert_eq!(trigger_path.as_deref(), Some("/api/manual/deploy-image"));
+    let units: Vec<String> = sqlx::query_scalar("SELECT unit FROM task_units WHERE task_id = ?")
+        .bind(&task_id)
+        .fetch_all(&pool)
+        .await?;
+    assert_eq!(units, vec!["svc-alpha.service"]);
+
+    let mut cmd = env.command();
+    cmd.arg("trigger-image")
+        .arg("ghcr.io/koha/other")
+        .arg("--dry-run")
+        .arg("--json")
+        .env("PODUP_CONTAINER_DIR", &container_dir);
+    let result = env.run_command(cmd)?;
+    assert!(result.status.success(), "stderr: {}", result.stderr);
+    let summary: Value = serde_json::from_str(result.stdout.trim())?;
+    assert_eq!(summary["total"], 1);
+    assert_eq!(summary["results"][0]["unit"], "svc-beta.service");
+
+    Ok(())
+}
+
+async fn scenario_scheduler_loadavg_deferral() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+    env.clear_mock_log()?;
+
+    let run_scheduler = |iterations: &str, load: &str| -> AnyResult<CommandResult> {
+        let mut cmd = env.command();
+        cmd.arg("scheduler")
+            .arg("--interval")
+            .arg("1")
+            .arg("--max-iterations")
+            .arg(iterations)
+            .env("PODUP_MAX_LOADAVG", "2")
+            .env("PODUP_LOADAVG_MOCK", load);
+        env.run_command(cmd)
+    };
+
+    let output = run_scheduler("2", "5.5")?;
+    assert!(output.status.success(), "stderr: {}", output.stderr);
+    assert!(
+        !env.read_mock_log()?
+            .iter()
+            .any(|line| line.contains("systemctl --user start podman-auto-update.service")),
+        "deferred scheduler task must not run"
+    );
+
+    let pool = env.connect_db().await?;
+    let queued: Vec<String> = sqlx::query_scalar(
+        "SELECT task_id FROM tasks WHERE kind = 'scheduler' AND status = 'queued'",
+    )
+    .fetch_all(&pool)
+    .await?;
+    assert_eq!(queued.len(), 1, "later ticks reuse the deferred task");
+    let deferred_id = queued[0].clone();
+
+    let events = env.fetch_events(&pool).await?;
+    let deferred = events
+        .iter()
+        .find(|e| e.action == "task-deferred")
+        .expect("task-deferred event recorded");
+    assert_eq!(deferred.meta["reason"], "loadavg");
+    assert_eq!(deferred.meta["task_id"], deferred_id.as_str());
+    assert!(
+        events
+            .iter()
+            .any(|e| e.action == "scheduler" && e.meta["reason"] == "deferred"),
+        "second tick skipped while the first task is deferred"
+    );
+
+    // Manual tasks bypass the guard by default.
+    let manual = env.send_request_with_env(
+        HttpRequest::post("/api/manual/trigger")
+            .header("content-type", "application/json")
+            .header("x-podup-csrf", "1")
+            .body(
+                json!({ "units": ["svc-alpha.service"] })
+                    .to_string()
+                    .into_bytes(),
+            ),
+        |cmd| {
+            cmd.env("PODUP_MAX_LOADAVG", "2")
+                .env("PODUP_LOADAVG_MOCK", "5.5");
+        },
+    )?;
+    assert_eq!(manual.status, 202, "{}", manual.body_text());
+    let manual_task = manual.json_body()?["task_id"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    let status: String = sqlx::query_scalar("SELECT status FROM tasks WHERE task_id = ?")
+        .bind(&manual_task)
+        .fetch_one(&pool)
+        .await?;
+    assert_ne!(status, "queued");
+
+    let output = run_scheduler("1", "0.5")?;
+    assert!(output.status.success(), "stderr: {}", output.stderr);
+    let detail = env.send_request(HttpRequest::get(&format!("/api/tasks/{deferred_id}")))?;
+    let detail = detail.json_body()?;
+    assert_ne!(detail["status"], "queued", "{detail}");
+    let logs = detail["logs"].as_array().cloned().unwrap_or_default();
+    assert!(logs.iter().any(|log| log["action"] == "task-deferred"));
+    assert!(logs.iter().any(|log| log["action"] == "task-dequeued"));
+
+    Ok(())
+}
+
+async fn scenario_tasks_stop_all_cancels_queued() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let pool = env.connect_db().await?;
+    let now = current_unix_secs() as i64;
+    sqlx::query(
+        "INSERT INTO tasks (task_id, kind, status, created_at, summary, meta, trigger_source, can_stop) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind("stop-all-queued")
+    .bind("manual")
+    .bind("queued")
+    .bind(now)
+    .bind("waiting for a slot")
+    .bind("{}")
+    .bind("test")
+    .bind(1_i64)
+    .execute(&pool)
+    .await?;
+
+    let response =
+        env.send_request(HttpRequest::post("/api/tasks/stop-all").header("x-podup-csrf", "1"))?;
+    assert_eq!(response.status, 200, "{}", response.body_text());
+    let body = response.json_body()?;
+    assert_eq!(body["cancelled"], 1);
+    assert!(
+        body["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|entry| entry["task_id"] == "stop-all-queued"
+                && entry["result"] == "cancelled-queued"),
+        "{body}"
+    );
+
+    let status: String = sqlx::query_scalar("SELECT status FROM tasks WHERE task_id = ?")
+        .bind("stop-all-queued")
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(status, "cancelled");
+
+    Ok(())
+}
+
+async fn scenario_api_error_envelope() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let missing_csrf = env.send_request(
+        HttpRequest::post("/api/tasks/tsk_missing/stop")
+            .header("content-type", "application/json")
+            .body(b"{}".to_vec()),
+    )?;
+    assert_eq!(missing_csrf.status, 403, "{}", missing_csrf.body_text());
+    let body = missing_csrf.json_body()?;
+    assert_eq!(body["error"]["code"], "csrf-required");
+    assert_eq!(body["error"]["details"]["field"], "x-podup-csrf");
+
+    let missing_task = env.send_request(HttpRequest::get("/api/tasks/tsk_missing"))?;
+    assert_eq!(missing_task.status, 404, "{}", missing_task.body_text());
+    let body = missing_task.json_body()?;
+    assert_eq!(body["error"]["code"], "task-not-found");
+    assert_eq!(body["error"]["message"], "task not found");
+
+    let unknown = env.send_request(HttpRequest::get("/api/does-not-exist"))?;
+    assert_eq!(unknown.status, 404, "{}", unknown.body_text());
+    assert_eq!(unknown.json_body()?["error"]["code"], "not-found");
+
+    let wrong_method =
+        env.send_request(HttpRequest::post("/api/events").header("x-podup-csrf", "1"))?;
+    assert_eq!(wrong_method.status, 405, "{}", wrong_method.body_text());
+    assert_eq!(
+        wrong_method.headers.get("allow").map(String::as_str),
+        Some("GET")
+    );
+    assert_eq!(
+        wrong_method.json_body()?["error"]["code"],
+        "method-not-allowed"
+    );
+
+    let pool = env.connect_db().await?;
+    let events = env.fetch_events(&pool).await?;
+    assert!(
+        events
+            .iter()
+            .any(|event| event.status == 404 && event.meta["code"] == "task-not-found")
+    );
+
+    Ok(())
+}
+
+async fn scenario_scheduler_unit_max_stale() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let container_dir = env.state_dir.join("containers/systemd");
+    fs::create_dir_all(&container_dir)?;
+    fs::write(
+        container_dir.join("svc-alpha.container"),
+        b"[Container]\nImage=ghcr.io/koha/svc-alpha:latest\n",
+    )?;
+    let ps_json = json!([
+        {
+            "Id": "cid-alpha",
+            "Created": 1000,
+            "State": "running",
+            "ImageID": "img-same",
+            "Labels": { "io.podman.systemd.unit": "svc-alpha.service" }
+        }
+    ]);
+    let inspect_json = json!([
+        {
+            "Id": "img-same",
+            "RepoTags": ["ghcr.io/koha/svc-alpha:latest"],
+            "RepoDigests": ["ghcr.io/koha/svc-alpha@sha256:bbbbbbbb"],
+            "Digest": "sha256:bbbbbbbb"
+        }
+    ]);
+
+    let run_tick = || -> AnyResult<bool> {
+        env.clear_mock_log()?;
+        let mut cmd = env.command();
+        cmd.arg("scheduler")
+            .arg("--interval")
+            .arg("1")
+            .arg("--max-iterations")
+            .arg("1")
+            .env("PODUP_CONTAINER_DIR", &container_dir)
+            .env("PODUP_MANUAL_UNITS", "svc-alpha.service")
+            .env("PODUP_UNIT_MAX_STALE_SECS", "3600")
+            .env("MOCK_PODMAN_PS_JSON", ps_json.to_string())
+            .env("MOCK_PODMAN_IMAGE_INSPECT_JSON", inspect_json.to_string())
+            .env(
+                "PODUP_REGISTRY_DIGEST_MOCK",
+                json!({ "ghcr.io/koha/svc-alpha:latest": "sha256:bbbbbbbb" }).to_string(),
+            );
+        let output = env.run_command(cmd)?;
+        assert!(output.status.success(), "stderr: {}", output.stderr);
+        Ok(env
+            .read_mock_log()?
+            .iter()
+            .any(|line| line.contains("systemctl --user start podman-auto-update.service")))
+    };
+
+    assert!(
+        run_tick()?,
+        "a never-checked unit must be verified despite matching digests"
+    );
+    let pool = env.connect_db().await?;
+    let checked_at: i64 =
+        sqlx::query_scalar("SELECT last_checked_at FROM unit_check_state WHERE unit = ?")
+            .bind("svc-alpha.service")
+            .fetch_one(&pool)
+            .await?;
+    assert!(checked_at > 0);
+    let summary: String = sqlx::query_scalar(
+        "SELECT summary FROM task_logs WHERE action = 'scheduler-stale-check' AND unit = ?",
+    )
+    .bind("svc-alpha.service")
+    .fetch_one(&pool)
+    .await?;
+    assert!(summary.contains("never checked"), "{summary}");
+
+    assert!(
+        !run_tick()?,
+        "a recently checked unit follows the digest gate"
+    );
+
+    sqlx::query("UPDATE unit_check_state SET last_checked_at = 0")
+        .execute(&pool)
+        .await?;
+    assert!(run_tick()?, "a unit past the max age is force-checked");
+    let stale = env
+        .fetch_events(&pool)
+        .await?
+        .into_iter()
+        .find(|row| row.action == "scheduler" && row.meta["status"] == "queued");
+    assert!(stale.is_some());
+
+    Ok(())
+}
+
+async fn scenario_secret_file_env() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let secret_path = env.state_dir.join("gh-webhook-secret");
+    fs::write(&secret_path, "from-file\n")?;
+    let settings = env.send_request_with_env(HttpRequest::get("/api/settings"), |cmd| {
+        cmd.env_remove("PODUP_GH_WEBHOOK_SECRET")
+            .env("PODUP_GH_WEBHOOK_SECRET_FILE", &secret_path);
+    })?;
+    assert_eq!(settings.status, 200, "{}", settings.body_text());
+    assert_eq!(
+        settings.json_body()?["env"]["PODUP_GH_WEBHOOK_SECRET_configured"],
+        Value::from(true)
+    );
+
+    let mut cmd = env.command();
+    cmd.arg("validate")
+        .env("PODUP_TOKEN_FILE", env.state_dir.join("no-such-secret"));
+    let output = env.run_command(cmd)?;
+    assert!(!output.status.success());
+    assert!(
+        output.stderr.contains("PODUP_TOKEN_FILE"),
+        "stderr: {}",
+        output.stderr
+    );
+
+    Ok(())
+}
+
+async fn scenario_token_hook() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+    env.clear_mock_log()?;
+
+    let container_dir = env.state_dir.join("containers/systemd");
+    fs::create_dir_all(&container_dir)?;
+    fs::write(
+        container_dir.join("svc-alpha.container"),
+        b"[Container]\nImage=ghcr.io/koha/svc-alpha:latest\n",
+    )?;
+
+    let wrong = env.send_request(HttpRequest::post("/hook/not-the-token/svc-alpha"))?;
+    assert_eq!(wrong.status, 401, "{}", wrong.body_text());
+
+    let unset = env.send_request_with_env(
+        HttpRequest::post(&format!("/hook/{}/svc-alpha", env.manual_token)),
+        |cmd| {
+            cmd.env_remove("PODUP_TOKEN");
+        },
+    )?;
+    assert_eq!(unset.status, 500, "{}", unset.body_text());
+
+    let accepted = env.send_request_with_env(
+        HttpRequest::post(&format!("/hook/{}/svc-alpha", env.manual_token)),
+        |cmd| {
+            cmd.env("PODUP_CONTAINER_DIR", &container_dir);
+            configure_image_verify_mocks(cmd);
+        },
+    )?;
+    assert_eq!(accepted.status, 202, "{}", accepted.body_text());
+    assert!(
+        env.read_mock_log()?
+            .iter()
+            .any(|line| line.contains("--unit=webhook-task-")),
+        "token hook dispatches the webhook pipeline"
+    );
+
+    let pool = env.connect_db().await?;
+    let paths: Vec<Option<String>> = sqlx::query_scalar("SELECT path FROM event_log")
+        .fetch_all(&pool)
+        .await?;
+    assert!(paths.iter().flatten().any(|p| p.starts_with("/hook/")));
+    let dump: Vec<String> = sqlx::query_scalar("SELECT COALESCE(path, '') || meta FROM event_log")
+        .fetch_all(&pool)
+        .await?;
+    assert!(
+        dump.iter().all(|row| !row.contains(&env.manual_token)),
+        "token leaked into event log"
+    );
+
+    Ok(())
+}
+
+async fn scenario_scheduler_pause() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let post = |path: &str, body: Value| {
+        HttpRequest::post(path)
+            .header("content-type", "application/json")
+            .header("x-podup-csrf", "1")
+            .body(body.to_string().into_bytes())
+    };
+
+    let missing_csrf = env.send_request(HttpRequest::post("/api/scheduler/pause"))?;
+    assert_eq!(missing_csrf.status, 403, "{}", missing_csrf.body_text());
+
+    let paused = env.send_request(post(
+        "/api/scheduler/pause",
+        json!({ "reason": "registry outage" }),
+    ))?;
+    assert_eq!(paused.status, 200, "{}", paused.body_text());
+    assert_eq!(paused.json_body()?["paused"], Value::from(true));
+
+    let settings = env
+        .send_request(HttpRequest::get("/api/settings"))?
+        .json_body()?;
+    assert_eq!(settings["scheduler"]["pause"]["paused"], Value::from(true));
+    assert_eq!(
+        settings["scheduler"]["pause"]["reason"],
+        Value::from("registry outage")
+    );
+
+    let run_tick = || -> AnyResult<bool> {
+        env.clear_mock_log()?;
+        let mut cmd = env.command();
+        cmd.arg("scheduler")
+            .arg("--interval")
+            .arg("1")
+            .arg("--max-iterations")
+            .arg("1")
+            .env("PODUP_SCHEDULER_FORCE", "1");
+        let output = env.run_command(cmd)?;
+        assert!(output.status.success(), "stderr: {}", output.stderr);
+        Ok(env
+            .read_mock_log()?
+            .iter()
+            .any(|line| line.contains("systemctl --user start podman-auto-update.service")))
+    };
+
+    assert!(!run_tick()?, "a paused scheduler must not trigger");
+    let pool = env.connect_db().await?;
+    let skipped = env
+        .fetch_events(&pool)
+        .await?
+        .into_iter()
+        .find(|row| row.action == "scheduler")
+        .expect("scheduler event recorded");
+    assert_eq!(skipped.meta["reason"], "paused");
+    let tasks: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tasks WHERE kind = 'scheduler'")
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(tasks, 0);
+
+    let resumed = env.send_request(post("/api/scheduler/resume", json!({})))?;
+    assert_eq!(resumed.status, 200, "{}", resumed.body_text());
+    assert_eq!(resumed.json_body()?["paused"], Value::from(false));
+    assert!(run_tick()?, "a resumed scheduler triggers again");
+
+    Ok(())
+}
+
+async fn scenario_manual_service_plan() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+    env.clear_mock_log()?;
+
+    let container_dir = env.state_dir.join("containers/systemd");
+    fs::create_dir_all(&container_dir)?;
+    fs::write(
+        container_dir.join("svc-alpha.container"),
+        b"[Container]\nImage=ghcr.io/koha/svc-alpha:stable\nAutoupdate=registry\n",
+    )?;
+
+    let ps_json = json!([
+        {
+            "Id": "cid-alpha-1",
+            "ImageID": "img-alpha-1",
+            "Created": 1000,
+            "State": "running",
+            "Labels": { "io.podman.systemd.unit": "svc-alpha.service" }
+        }
+    ]);
+    let inspect_json = json!([
+        { "Id": "img-alpha-1", "RepoDigests": [ "ghcr.io/koha/svc-alpha@sha256:aaaaaaaa" ] }
+    ]);
+    let registry_mock = json!({ "ghcr.io/koha/svc-alpha:stable": "sha256:bbbbbbbb" });
+
+    let resp = env.send_request_with_env(
+        HttpRequest::get("/api/manual/services/svc-alpha/plan"),
+        |cmd| {
+            cmd.env("PODUP_CONTAINER_DIR", &container_dir);
+            cmd.env("MOCK_PODMAN_PS_JSON", ps_json.to_string());
+            cmd.env("MOCK_PODMAN_IMAGE_INSPECT_JSON", inspect_json.to_string());
+            cmd.env("PODUP_REGISTRY_DIGEST_MOCK", registry_mock.to_string());
+        },
+    )?;
+    assert_eq!(resp.status, 200);
+    let body = resp.json_body()?;
+    assert_eq!(body["unit"], Value::from("svc-alpha.service"));
+    assert_eq!(body["image"], Value::from("ghcr.io/koha/svc-alpha:stable"));
+    assert_eq!(body["running_digest"], Value::from("sha256:aaaaaaaa"));
+    assert_eq!(body["remote_digest"], Value::from("sha256:bbbbbbbb"));
+    assert_eq!(body["would_change"], Value::Bool(true));
+    assert_eq!(body["strategy"], Value::from("restart"));
+    assert_eq!(
+        body["commands"],
+        json!([
+            "podman pull ghcr.io/koha/svc-alpha:stable",
+            "systemctl --user restart svc-alpha.service"
+        ])
+    );
+
+    let log = env.read_mock_log()?;
+    assert!(
+        !log.iter()
+            .any(|line| line.starts_with("podman pull") || line.contains("restart")),
+        "plan must not execute commands: {log:?}"
+    );
+
+    let up_to_date = json!({ "ghcr.io/koha/svc-alpha:stable": "sha256:aaaaaaaa" });
+    let resp = env.send_request_with_env(
+        HttpRequest::get("/api/manual/services/svc-alpha/plan?refresh=1"),
+        |cmd| {
+            cmd.env("PODUP_CONTAINER_DIR", &container_dir);
+            cmd.env("MOCK_PODMAN_PS_JSON", ps_json.to_string());
+            cmd.env("MOCK_PODMAN_IMAGE_INSPECT_JSON", inspect_json.to_string());
+            cmd.env("PODUP_REGISTRY_DIGEST_MOCK", up_to_date.to_string());
+        },
+    )?;
+    assert_eq!(resp.status, 200);
+    let body = resp.json_body()?;
+    assert_eq!(body["would_change"], Value::Bool(false));
+    assert_eq!(body["reason"], Value::from("up-to-date"));
+
+    let resp = env.send_request(HttpRequest::get("/api/manual/services/svc-missing/plan"))?;
+    assert_eq!(resp.status, 404);
+
+    // The audit event splits the request time into db/registry/command phases.
+    let pool = env.connect_db().await?;
+    let events = env.fetch_events(&pool).await?;
+    let plan_event = events
+        .iter()
+        .find(|row| row.action == "manual-service-plan" && row.status == 200)
+        .expect("manual-service-plan event");
+    let timings = &plan_event.meta["timings"];
+    for key in ["db_ms", "registry_ms", "command_ms"] {
+        assert!(timings[key].is_u64(), "missing {key}: {timings}");
+    }
+
+    Ok(())
+}
+
+async fn scenario_task_tags() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let create = |mut body: Value| {
+        body["units"] = json!(["svc-alpha.service"]);
+        HttpRequest::post("/api/tasks")
+            .header("content-type", "application/json")
+            .header("x-podup-csrf", "1")
+            .body(body.to_string().into_bytes())
+    };
+
+    let prod = env.send_request(create(
+        json!({ "tags": ["env=prod", " deploy ", "env=prod"] }),
+    ))?;
+    assert_eq!(prod.status, 200, "{}", prod.body_text());
+    let prod_id = prod.json_body()?["task_id"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    let staging = env.send_request(create(json!({ "tags": ["env=staging", "deploy"] })))?;
+    assert_eq!(staging.status, 200, "{}", staging.body_text());
+    let untagged = env.send_request(create(json!({})))?;
+    assert_eq!(untagged.status, 200);
+
+    let detail = env
+        .send_request(HttpRequest::get(&format!("/api/tasks/{prod_id}")))?
+        .json_body()?;
+    assert_eq!(detail["tags"], json!(["deploy", "env=prod"]));
+
+    let list = env
+        .send_request(HttpRequest::get("/api/tasks?tag=env%3Dprod"))?
+        .json_body()?;
+    assert_eq!(list["total"], Value::from(1));
+    assert_eq!(list["tasks"][0]["task_id"], Value::from(prod_id.as_str()));
+    assert_eq!(list["tasks"][0]["tags"], json!(["deploy", "env=prod"]));
+
+    let both = env
+        .send_request(HttpRequest::get("/api/tasks?tag=deploy&tag=env%3Dstaging"))?
+        .json_body()?;
+    assert_eq!(both["total"], Value::from(1));
+
+    let all = env
+        .send_request(HttpRequest::get("/api/tasks"))?
+        .json_body()?;
+    assert!(
+        all["tasks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|task| task.get("tags").is_none()),
+        "untagged tasks omit the tags field: {all}"
+    );
+
+    let invalid = env.send_request(create(json!({ "tags": ["bad tag"] })))?;
+    assert_eq!(invalid.status, 400, "{}", invalid.body_text());
+    assert_eq!(
+        invalid.json_body()?["error"]["code"],
+        Value::from("invalid-request")
+    );
+
+    let trigger = env.send_request(
+        HttpRequest::post("/api/manual/trigger")
+            .header("content-type", "application/json")
+            .header("x-podup-csrf", "1")
+            .body(
+                json!({ "units": ["svc-alpha.service"], "tags": ["release"] })
+                    .to_string()
+                    .into_bytes(),
+            ),
+    )?;
+    assert_eq!(trigger.status, 202, "{}", trigger.body_text());
+    let trigger_id = trigger.json_body()?["task_id"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    let list = env
+        .send_request(HttpRequest::get("/api/tasks?tag=release"))?
+        .json_body()?;
+    assert_eq!(list["total"], Value::from(1));
+    assert_eq!(
+        list["tasks"][0]["task_id"],
+        Value::from(trigger_id.as_str())
+    );
+
+    Ok(())
+}
+
+async fn scenario_podman_health_ttl() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+    env.clear_mock_log()?;
+
+    // A fresh cached failure is reused without probing podman again.
+    let cache = env.state_dir.join("podman-health.json");
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    fs::write(
+        &cache,
+        json!({ "checked_at": now, "error": "podman unavailable: exit=125" }).to_string(),
+    )?;
+
+    let cached = env.send_request_with_env(HttpRequest::get("/health"), |cmd| {
+        cmd.env("PODUP_PODMAN_HEALTH_TTL_SECS", "300");
+    })?;
+    assert_eq!(cached.status, 503, "{}", cached.body_text());
+    assert_eq!(
+        cached.json_body()?["podman"]["error"],
+        Value::from("podman unavailable: exit=125")
+    );
+    assert!(
+        !env.read_mock_log()?
+            .iter()
+            .any(|line| line.starts_with("podman --version")),
+        "cached result must not re-probe podman"
+    );
+
+    // refresh=1 re-probes and rewrites the shared cache.
+    let refreshed = env.send_request_with_env(HttpRequest::get("/health?refresh=1"), |cmd| {
+        cmd.env("PODUP_PODMAN_HEALTH_TTL_SECS", "300");
+    })?;
+    assert_eq!(refreshed.status, 200, "{}", refreshed.body_text());
+    assert_eq!(refreshed.json_body()?["podman"]["ok"], Value::Bool(true));
+    let stored: Value = serde_json::from_str(&fs::read_to_string(&cache)?)?;
+    assert_eq!(stored["error"], Value::Null);
+
+    // Without a TTL the cache file is ignored.
+    fs::write(
+        &cache,
+        json!({ "checked_at": now, "error": "podman unavailable: exit=125" }).to_string(),
+    )?;
+    let uncached = env.send_request(HttpRequest::get("/health"))?;
+    assert_eq!(uncached.status, 200, "{}", uncached.body_text());
+
+    Ok(())
+}
+
+async fn scenario_events_sse() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+    let pool = env.connect_db().await?;
+
+    let mut probe_ids: Vec<i64> = Vec::new();
+    for (idx, action) in ["tail-probe", "tail-other", "tail-probe"]
+        .iter()
+        .enumerate()
+    {
+        let id = sqlx::query(
+            "INSERT INTO event_log (request_id, ts, method, path, status, action, duration_ms, meta, task_id) \
+             VALUES (?, ?, 'GET', '/api/tail', 200, ?, 1, '{}', NULL)",
+        )
+        .bind(format!("tail-{idx}"))
+        .bind(2_000 + idx as i64)
+        .bind(*action)
+        .execute(&pool)
+        .await?
+        .last_insert_rowid();
+        if *action == "tail-probe" {
+            probe_ids.push(id);
+        }
+    }
+
+    let streamed_ids = |body: &str| -> Vec<i64> {
+        body.lines()
+            .filter_map(|line| line.strip_prefix("id: "))
+            .filter_map(|id| id.parse::<i64>().ok())
+            .collect()
+    };
+
+    let response = env.send_request(HttpRequest::get(
+        "/sse/events?action=tail-probe&since_id=0&max_secs=1",
+    ))?;
+    assert_eq!(response.status, 200, "{}", response.body_text());
+    assert!(response.headers["content-type"].starts_with("text/event-stream"));
+    let body = response.body_text();
+    assert_eq!(streamed_ids(&body), probe_ids, "{body}");
+    assert!(body.contains("\"action\":\"tail-probe\""));
+    assert!(!body.contains("tail-other"));
+    assert!(body.ends_with("event: end\ndata: timeout"), "{body}");
+
+    // Last-Event-ID wins over since_id when an EventSource reconnects.
+    let resumed = env.send_request(
+        HttpRequest::get("/sse/events?action=tail-probe&since_id=0&max_secs=1")
+            .header("Last-Event-ID", &probe_ids[0].to_string()),
+    )?;
+    assert_eq!(streamed_ids(&resumed.body_text()), vec![probe_ids[1]]);
+
+    // Without a cursor only events written after the stream opened are sent.
+    let fresh = env.send_request(HttpRequest::get("/sse/events?action=tail-probe&max_secs=1"))?;
+    assert!(streamed_ids(&fresh.body_text()).is_empty());
+
+    let denied = env.send_request_with_env(HttpRequest::get("/sse/events"), |cmd| {
+        cmd.env("PODUP_DEV_OPEN_ADMIN", "0");
+        cmd.env("PODUP_FWD_AUTH_HEADER", "x-test-admin");
+        cmd.env("PODUP_FWD_AUTH_ADMIN_VALUE", "yes");
+    })?;
+    assert_eq!(denied.status, 401);
+
+    let events = env.fetch_events(&pool).await?;
+    let audit = events
+        .iter()
+        .find(|row| row.action == "events-sse" && row.status == 200)
+        .expect("events-sse audit row");
+    assert_eq!(audit.meta["events_sent"], json!(2));
+    assert_eq!(audit.meta["reason"], json!("timeout"));
+
+    Ok(())
+}
+
+async fn scenario_task_create_validation() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let create = |body: Value| {
+        HttpRequest::post("/api/tasks")
+            .header("content-type", "application/json")
+            .header("x-podup-csrf", "1")
+            .body(body.to_string().into_bytes())
+    };
+    let error_field = |resp: &HttpResponse| -> AnyResult<Value> {
+        let body = resp.json_body()?;
+        assert_eq!(body["error"]["code"], Value::from("invalid-request"));
+        Ok(body["error"]["details"]["field"].clone())
+    };
+
+    let unknown = env.send_request(create(json!({
+        "kind": "junk",
+        "units": ["svc-alpha.service"],
+    })))?;
+    assert_eq!(unknown.status, 400, "{}", unknown.body_text());
+    assert_eq!(error_field(&unknown)?, Value::from("kind"));
+
+    let missing_units = env.send_request(create(json!({ "kind": "manual" })))?;
+    assert_eq!(missing_units.status, 400, "{}", missing_units.body_text());
+    assert_eq!(error_field(&missing_units)?, Value::from("units"));
+
+    let bad_unit = env.send_request(create(json!({ "units": ["../etc/passwd"] })))?;
+    assert_eq!(bad_unit.status, 400, "{}", bad_unit.body_text());
+    assert_eq!(error_field(&bad_unit)?, Value::from("units"));
+
+    // Unit-less kinds may omit units and store none.
+    let maintenance = env.send_request(create(json!({ "kind": "maintenance" })))?;
+    assert_eq!(maintenance.status, 200, "{}", maintenance.body_text());
+    let task_id = maintenance.json_body()?["task_id"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    let pool = env.connect_db().await?;
+    let stored_units: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM task_units WHERE task_id = ?")
+        .bind(&task_id)
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(stored_units, 0);
+
+    // The allow-list narrows the accepted kinds.
+    let restrict = |cmd: &mut Command| {
+        cmd.env("PODUP_TASK_CREATE_KINDS", "maintenance");
+    };
+    let manual = env.send_request_with_env(
+        create(json!({ "kind": "manual", "units": ["svc-alpha.service"] })),
+        restrict,
+    )?;
+    assert_eq!(manual.status, 400, "{}", manual.body_text());
+    assert_eq!(
+        manual.json_body()?["error"]["details"]["allowed"],
+        json!(["maintenance"])
+    );
+    let allowed = env.send_request_with_env(create(json!({ "kind": "maintenance" })), restrict)?;
+    assert_eq!(allowed.status, 200, "{}", allowed.body_text());
+
+    Ok(())
+}
+
+async fn scenario_units_discover() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let container_dir = env.state_dir.join("containers/systemd");
+    fs::create_dir_all(&container_dir)?;
+    fs::write(
+        container_dir.join("svc-gamma.container"),
+        b"[Container]\nImage=ghcr.io/koha/svc-gamma:main\nAutoupdate=registry",
+    )?;
+
+    let discover = |csrf: bool| -> AnyResult<HttpResponse> {
+        let mut request = HttpRequest::post("/api/units/discover");
+        if csrf {
+            request = request.header("x-podup-csrf", "1");
+        }
+        env.send_request_with_env(request, |cmd| {
+            cmd.env("PODUP_CONTAINER_DIR", &container_dir);
+        })
+    };
+
+    let missing_csrf = discover(false)?;
+    assert_eq!(missing_csrf.status, 403, "{}", missing_csrf.body_text());
+
+    let get = env.send_request(HttpRequest::get("/api/units/discover"))?;
+    assert_eq!(get.status, 405);
+
+    let first = discover(true)?;
+    assert_eq!(first.status, 200, "{}", first.body_text());
+    let body = first.json_body()?;
+    assert!(
+        body["units"].as_array().is_some_and(|units| units
+            .iter()
+            .any(|u| u["unit"] == "svc-gamma.service" && u["source"] == "dir")),
+        "{body}"
+    );
+    assert_eq!(body["counts"]["sources"]["dir"], Value::from(1));
+
+    fs::write(
+        container_dir.join("svc-zeta.container"),
+        b"[Container]\nImage=ghcr.io/koha/svc-zeta:main\nAutoupdate=registry",
+    )?;
+    let second = discover(true)?.json_body()?;
+    assert_eq!(second["added"], json!(["svc-zeta.service"]));
+    assert_eq!(second["counts"]["added"], Value::from(1));
+    assert_eq!(second["counts"]["sources"]["dir"], Value::from(2));
+
+    // The legacy query parameter still works but is flagged.
+    let legacy = env.send_request(HttpRequest::get("/api/manual/services?discover=1"))?;
+    assert_eq!(legacy.status, 200);
+    assert!(legacy.json_body()?["discovered"]["deprecated"].is_string());
+    let plain = env.send_request(HttpRequest::get("/api/manual/services"))?;
+    assert!(plain.json_body()?["discovered"].get("deprecated").is_none());
+
+    Ok(())
+}
+
+async fn scenario_trigger_units_stdin() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.clear_mock_log()?;
+
+    let run = |args: &[&str], input: &str| -> AnyResult<CommandResult> {
+        let mut cmd = env.command();
+        cmd.arg("trigger-units").args(args);
+        cmd.stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let mut child = cmd.spawn()?;
+        child
+            .stdin
+            .take()
+            .expect("stdin available")
+            .write_all(input.as_bytes())?;
+        let output = child.wait_with_output()?;
+        Ok(CommandResult {
+            status: output.status,
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    };
+
+    let result = run(
+        &["-", "--dry-run", "--json", "--caller", "ci"],
+        "svc-alpha\nbad unit!.service\n# skipped\nsvc-beta.service, svc-alpha.service\n",
+    )?;
+    assert!(
+        result.status.success(),
+        "status={} stderr={}",
+        result.status,
+        result.stderr
+    );
+    assert!(
+        result
+            .stderr
+            .contains("stdin line 2: unknown unit identifier: bad unit!.service"),
+        "{}",
+        result.stderr
+    );
+    let body: Value = serde_json::from_str(result.stdout.trim())?;
+    let units: Vec<&str> = body["results"]
+        .as_array()
+        .map(|results| results.iter().filter_map(|r| r["unit"].as_str()).collect())
+        .unwrap_or_default();
+    assert_eq!(units, vec!["svc-alpha.service", "svc-beta.service"]);
+    assert!(
+        env.read_mock_log()?
+            .iter()
+            .all(|line| !line.contains("systemctl")),
+        "dry-run must not restart anything"
+    );
+
+    // An empty list never falls back to every configured unit.
+    let empty = run(&["--stdin", "--dry-run"], "\n# nothing\n")?;
+    assert_eq!(empty.status.code(), Some(2), "{}", empty.stdout);
+    assert!(empty.stderr.contains("No units resolved"));
+
+    Ok(())
+}
+
+async fn scenario_github_event_prefilter() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    // Pings are answered before HMAC so GitHub shows the hook as healthy.
+    let ping = env.send_request(
+        HttpRequest::post("/github-package-update/svc-alpha")
+            .header("content-type", "application/json")
+            .header("x-github-event", "ping")
+            .header("x-github-delivery", "ping-delivery")
+            .body(
+                json!({ "zen": "Keep it logically awesome.", "hook_id": 42 })
+                    .to_string()
+                    .into_bytes(),
+            ),
+    )?;
+    assert_eq!(ping.status, 200, "{}", ping.body_text());
+    let body = ping.json_body()?;
+    assert_eq!(body["event"], "ping");
+    assert_eq!(body["zen"], "Keep it logically awesome.");
+    assert_eq!(body["hook_id"], 42);
+
+    // Events that never carry an image are skipped without a signature check.
+    let star = env.send_request(
+        HttpRequest::post("/github-package-update/svc-alpha")
+            .header("x-github-event", "star")
+            .header("x-github-delivery", "star-delivery")
+            .body(b"{}".to_vec()),
+    )?;
+    assert_eq!(star.status, 202, "{}", star.body_text());
+
+    // An oversized Content-Length is refused before the body is read.
+    let oversized = env.send_request(
+        HttpRequest::post("/github-package-update/svc-alpha")
+            .header("x-github-event", "registry_package")
+            .header("content-length", &(26 * 1024 * 1024).to_string())
+            .body(b"{}".to_vec()),
+    )?;
+    assert_eq!(oversized.status, 413, "{}", oversized.body_text());
+
+    // Package events still require a valid signature.
+    let unsigned = env.send_request(
+        HttpRequest::post("/github-package-update/svc-alpha")
+            .header("x-github-event", "registry_package")
+            .header("x-github-delivery", "unsigned-delivery")
+            .body(github_registry_payload("koha", "svc-alpha", "main")),
+    )?;
+    assert_eq!(unsigned.status, 401);
+
+    let pool = env.connect_db().await?;
+    let events = env.fetch_events(&pool).await?;
+    assert!(events.iter().any(|row| row.action == "github-webhook"
+        && row.status == 200
+        && row.meta["reason"] == "ping"));
+    assert!(events.iter().any(|row| row.action == "ignored-event"
+        && row.status == 202
+        && row.meta["event"] == "star"
+        && row.meta["delivery"] == "star-delivery"));
 
     Ok(())
 }
@@ -1549,7 +4869,7 @@ async fn scenario_manual_service_upgrade_clone_fallback_create_command() -> AnyR
         .expect("task must include container-inspect log entry");
     let inspect_meta = inspect.get("meta").cloned().unwrap_or(Value::Null);
     assert!(
-        inspect_meta.get("stdout").is_none(),
+        inspect_meta.get("stdout_tail").is_none() && inspect_meta.get("stdout").is_none(),
         "container-inspect must not log stdout"
     );
 
@@ -1573,6 +4893,97 @@ async fn scenario_manual_service_upgrade_clone_fallback_create_command() -> AnyR
     Ok(())
 }
 
+async fn scenario_manual_service_image_override() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let container_dir = env.state_dir.join("containers/systemd");
+    fs::create_dir_all(&container_dir)?;
+    fs::write(
+        container_dir.join("svc-alpha.container"),
+        b"[Container]\nImage=ghcr.io/koha/svc-alpha:stable\n",
+    )?;
+    let with_dir = |cmd: &mut Command| {
+        cmd.env("PODUP_CONTAINER_DIR", &container_dir);
+    };
+    let find_alpha = |body: &Value| -> Value {
+        body["services"]
+            .as_array()
+            .and_then(|services| {
+                services
+                    .iter()
+                    .find(|s| s["unit"] == "svc-alpha.service")
+                    .cloned()
+            })
+            .expect("svc-alpha exists")
+    };
+
+    let resp = env.send_request_with_env(HttpRequest::get("/api/manual/services"), with_dir)?;
+    assert_eq!(resp.status, 200);
+    let svc = find_alpha(&resp.json_body()?);
+    assert_eq!(svc["default_image"], "ghcr.io/koha/svc-alpha:stable");
+    assert_eq!(svc["image_source"], "unit-file");
+
+    let invalid = env.send_request_with_env(
+        HttpRequest::new("PUT", "/api/manual/services/svc-alpha/image")
+            .header("x-podup-csrf", "1")
+            .header("content-type", "application/json")
+            .body(json!({ "image": "not-an-image" }).to_string().into_bytes()),
+        with_dir,
+    )?;
+    assert_eq!(invalid.status, 400, "{}", invalid.body_text());
+
+    let missing = env.send_request_with_env(
+        HttpRequest::new("PUT", "/api/manual/services/svc-unknown/image")
+            .header("x-podup-csrf", "1")
+            .header("content-type", "application/json")
+            .body(
+                json!({ "image": "ghcr.io/koha/svc-alpha:v2" })
+                    .to_string()
+                    .into_bytes(),
+            ),
+        with_dir,
+    )?;
+    assert_eq!(missing.status, 404);
+
+    let pinned = env.send_request_with_env(
+        HttpRequest::new("PUT", "/api/manual/services/svc-alpha/image")
+            .header("x-podup-csrf", "1")
+            .header("content-type", "application/json")
+            .body(
+                json!({ "image": "ghcr.io/koha/svc-alpha:v2" })
+                    .to_string()
+                    .into_bytes(),
+            ),
+        with_dir,
+    )?;
+    assert_eq!(pinned.status, 200, "{}", pinned.body_text());
+    let body = pinned.json_body()?;
+    assert_eq!(body["image"], "ghcr.io/koha/svc-alpha:v2");
+    assert_eq!(body["image_source"], "override");
+
+    let resp = env.send_request_with_env(HttpRequest::get("/api/manual/services"), with_dir)?;
+    let svc = find_alpha(&resp.json_body()?);
+    assert_eq!(svc["default_image"], "ghcr.io/koha/svc-alpha:v2");
+    assert_eq!(svc["image_source"], "override");
+    assert_eq!(svc["update"]["tag"], "v2");
+
+    let cleared = env.send_request_with_env(
+        HttpRequest::new("PUT", "/api/manual/services/svc-alpha/image")
+            .header("x-podup-csrf", "1")
+            .header("content-type", "application/json")
+            .body(json!({ "image": null }).to_string().into_bytes()),
+        with_dir,
+    )?;
+    assert_eq!(cleared.status, 200, "{}", cleared.body_text());
+    let body = cleared.json_body()?;
+    assert!(body["override"].is_null());
+    assert_eq!(body["image"], "ghcr.io/koha/svc-alpha:stable");
+    assert_eq!(body["image_source"], "unit-file");
+
+    Ok(())
+}
+
 async fn scenario_manual_services_update_tag_update_available() -> AnyResult<()> {
     let env = TestEnv::new()?;
     env.ensure_db_initialized().await?;
@@ -1978,7 +5389,7 @@ async fn scenario_manual_task_command_meta_and_unit_errors() -> AnyResult<()> {
         "restart-unit meta.exit should be non-empty"
     );
     let stderr = meta
-        .get("stderr")
+        .get("stderr_tail")
         .and_then(|v| v.as_str())
         .unwrap_or_default()
         .to_string();
@@ -2004,8 +5415,8 @@ async fn scenario_manual_task_command_meta_and_unit_errors() -> AnyResult<()> {
     );
     assert!(
         service_run_meta.get("command").is_none()
-            && service_run_meta.get("stdout").is_none()
-            && service_run_meta.get("stderr").is_none(),
+            && service_run_meta.get("stdout_tail").is_none()
+            && service_run_meta.get("stderr_tail").is_none(),
         "manual-service-run summary must not contain command/stdout/stderr"
     );
 
@@ -2093,7 +5504,7 @@ async fn scenario_manual_task_command_meta_and_unit_errors() -> AnyResult<()> {
         "restart-unit meta.exit should be non-empty"
     );
     let stderr = meta
-        .get("stderr")
+        .get("stderr_tail")
         .and_then(|v| v.as_str())
         .unwrap_or_default()
         .to_string();
@@ -2344,7 +5755,8 @@ async fn scenario_manual_dispatch_failure() -> AnyResult<()> {
         trigger.body_text()
     );
     let trigger_json = trigger.json_body()?;
-    let trigger_task_id = trigger_json["task_id"]
+    assert_eq!(trigger_json["error"]["code"], "dispatch-failed");
+    let trigger_task_id = trigger_json["error"]["details"]["task_id"]
         .as_str()
         .unwrap_or_default()
         .to_string();
@@ -2392,7 +5804,7 @@ async fn scenario_manual_dispatch_failure() -> AnyResult<()> {
         service.body_text()
     );
     let service_json = service.json_body()?;
-    let service_task_id = service_json["task_id"]
+    let service_task_id = service_json["error"]["details"]["task_id"]
         .as_str()
         .unwrap_or_default()
         .to_string();
@@ -2442,7 +5854,10 @@ async fn scenario_manual_dispatch_failure() -> AnyResult<()> {
         run.body_text()
     );
     let run_json = run.json_body()?;
-    let run_task_id = run_json["task_id"].as_str().unwrap_or_default().to_string();
+    let run_task_id = run_json["error"]["details"]["task_id"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
     assert!(
         !run_task_id.is_empty(),
         "manual auto-update run dispatch failure must include task_id"
@@ -2500,6 +5915,168 @@ async fn scenario_scheduler_loop() -> AnyResult<()> {
         .filter(|row| row.action == "scheduler")
         .collect();
     assert_eq!(scheduler_events.len(), 2);
+
+    let settings = env.send_request(HttpRequest::get("/api/settings"))?;
+    assert_eq!(settings.status, 200);
+    let state = settings.json_body()?["scheduler"]["state"].clone();
+    assert_eq!(state["iteration"], 2, "scheduler state: {state}");
+    assert_eq!(state["interval_secs"], 1);
+    assert!(state["last_tick_at"].as_i64().unwrap_or(0) > 0);
+    assert_eq!(state["last_tick_errored"], false);
+    assert!(state["last_task_id"].is_string());
+    assert_eq!(
+        state["next_tick_at"],
+        Value::Null,
+        "loop stopped after --max-iterations"
+    );
+    assert_eq!(state["alive"], false);
+    Ok(())
+}
+
+async fn scenario_scheduler_dry_run() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+    env.clear_mock_log()?;
+
+    let mut cmd = env.command();
+    cmd.arg("scheduler")
+        .arg("--interval")
+        .arg("1")
+        .arg("--max-iterations")
+        .arg("1")
+        .arg("--dry-run")
+        // Keep registry lookups offline; unknown digests still plan a trigger.
+        .env("HTTPS_PROXY", "http://127.0.0.1:9");
+    let output = env.run_command(cmd)?;
+    assert!(output.status.success(), "stderr: {}", output.stderr);
+
+    let log_lines = env.read_mock_log()?;
+    assert!(
+        !log_lines
+            .iter()
+            .any(|line| line.contains("podman-auto-update.service") && line.contains("start")),
+        "dry-run must not start the auto-update unit: {log_lines:?}"
+    );
+
+    let pool = env.connect_db().await?;
+    let event = env
+        .fetch_events(&pool)
+        .await?
+        .into_iter()
+        .find(|row| row.action == "scheduler")
+        .expect("scheduler event recorded");
+    assert_eq!(event.meta["status"], "dry-run");
+    assert_eq!(event.meta["dry_run"], true);
+    let task_id = event.meta["task_id"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+
+    let detail = env.send_request(HttpRequest::get(&format!("/api/tasks/{task_id}")))?;
+    assert_eq!(detail.status, 200);
+    let body = detail.json_body()?;
+    assert_eq!(body["status"], "succeeded");
+    assert!(
+        body["summary"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("would trigger"),
+        "summary: {}",
+        body["summary"]
+    );
+    let logs = body["logs"].as_array().cloned().unwrap_or_default();
+    assert!(
+        logs.iter()
+            .any(|entry| entry["action"] == "scheduler-plan"
+                && entry["meta"]["would_trigger"] == true),
+        "expected per-unit plan logs: {body}"
+    );
+
+    Ok(())
+}
+
+async fn scenario_scheduler_digest_gate() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let container_dir = env.state_dir.join("containers/systemd");
+    fs::create_dir_all(&container_dir)?;
+    fs::write(
+        container_dir.join("svc-alpha.container"),
+        b"[Container]\nImage=ghcr.io/koha/svc-alpha:latest\n",
+    )?;
+    let ps_json = json!([
+        {
+            "Id": "cid-alpha",
+            "Created": 1000,
+            "State": "running",
+            "ImageID": "img-same",
+            "Labels": { "io.podman.systemd.unit": "svc-alpha.service" }
+        }
+    ]);
+    let inspect_json = json!([
+        {
+            "Id": "img-same",
+            "RepoTags": ["ghcr.io/koha/svc-alpha:latest"],
+            "RepoDigests": ["ghcr.io/koha/svc-alpha@sha256:bbbbbbbb"],
+            "Digest": "sha256:bbbbbbbb"
+        }
+    ]);
+
+    let run_tick = |remote_digest: &str, force: bool| -> AnyResult<bool> {
+        env.clear_mock_log()?;
+        let mut cmd = env.command();
+        cmd.arg("scheduler")
+            .arg("--interval")
+            .arg("1")
+            .arg("--max-iterations")
+            .arg("1")
+            .env("PODUP_CONTAINER_DIR", &container_dir)
+            .env("PODUP_MANUAL_UNITS", "svc-alpha.service")
+            .env("MOCK_PODMAN_PS_JSON", ps_json.to_string())
+            .env("MOCK_PODMAN_IMAGE_INSPECT_JSON", inspect_json.to_string())
+            .env(
+                "PODUP_REGISTRY_DIGEST_MOCK",
+                json!({ "ghcr.io/koha/svc-alpha:latest": remote_digest }).to_string(),
+            );
+        if force {
+            cmd.env("PODUP_SCHEDULER_FORCE", "1");
+        }
+        let output = env.run_command(cmd)?;
+        assert!(output.status.success(), "stderr: {}", output.stderr);
+        Ok(env
+            .read_mock_log()?
+            .iter()
+            .any(|line| line.contains("systemctl --user start podman-auto-update.service")))
+    };
+
+    assert!(
+        !run_tick("sha256:bbbbbbbb", false)?,
+        "up-to-date digests must not trigger auto-update"
+    );
+    let pool = env.connect_db().await?;
+    let skipped = env
+        .fetch_events(&pool)
+        .await?
+        .into_iter()
+        .find(|row| row.action == "scheduler")
+        .expect("scheduler event recorded");
+    assert_eq!(skipped.meta["status"], "skipped");
+    assert_eq!(skipped.meta["plans"][0]["reason"], "up-to-date");
+
+    assert!(
+        run_tick("sha256:bbbbbbbb", true)?,
+        "PODUP_SCHEDULER_FORCE keeps the always-trigger behaviour"
+    );
+    // Age the cached digest so the next tick re-resolves it.
+    sqlx::query("UPDATE registry_digest_cache SET checked_at = 0")
+        .execute(&pool)
+        .await?;
+    assert!(
+        run_tick("sha256:cccccccc", false)?,
+        "a changed remote digest must trigger auto-update"
+    );
+
     Ok(())
 }
 
@@ -2551,24 +6128,334 @@ async fn scenario_scheduler_dispatch_failure() -> AnyResult<()> {
         .unwrap_or_default()
         .to_string();
     assert!(
-        !task_id.is_empty(),
-        "scheduler dispatch failure event must include task_id"
-    );
-
-    let detail = env.send_request(HttpRequest::get(&format!("/api/tasks/{task_id}")))?;
-    assert_eq!(
-        detail.status, 200,
-        "/api/tasks/:id should succeed for scheduler dispatch failure task"
+        !task_id.is_empty(),
+        "scheduler dispatch failure event must include task_id"
+    );
+
+    let detail = env.send_request(HttpRequest::get(&format!("/api/tasks/{task_id}")))?;
+    assert_eq!(
+        detail.status, 200,
+        "/api/tasks/:id should succeed for scheduler dispatch failure task"
+    );
+    let body = detail.json_body()?;
+    assert_eq!(body["status"], Value::from("failed"));
+    let logs = body["logs"].as_array().cloned().unwrap_or_default();
+    assert!(
+        logs.iter().any(
+            |entry| entry.get("action") == Some(&Value::from("task-dispatch-failed"))
+                && entry.get("status") == Some(&Value::from("failed"))
+        ),
+        "scheduler dispatch failure task should record task-dispatch-failed log entry"
+    );
+
+    Ok(())
+}
+
+async fn scenario_tasks_active() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let empty = env.send_request(HttpRequest::get("/api/tasks/active"))?;
+    assert_eq!(empty.status, 200);
+    assert_eq!(empty.json_body()?, json!({ "tasks": [] }));
+
+    let pool = env.connect_db().await?;
+    let now = current_unix_secs() as i64;
+    for (task_id, status) in [("active-running", "running"), ("active-done", "succeeded")] {
+        sqlx::query(
+            "INSERT INTO tasks (task_id, kind, status, created_at, started_at, summary, meta, trigger_source, can_stop) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(task_id)
+        .bind("manual")
+        .bind(status)
+        .bind(now)
+        .bind(now)
+        .bind("active task")
+        .bind("{}")
+        .bind("test")
+        .bind(1_i64)
+        .execute(&pool)
+        .await?;
+    }
+    for unit in ["svc-a.service", "svc-b.service"] {
+        sqlx::query("INSERT INTO task_units (task_id, unit, status) VALUES (?, ?, ?)")
+            .bind("active-running")
+            .bind(unit)
+            .bind("running")
+            .execute(&pool)
+            .await?;
+    }
+
+    let response = env.send_request(HttpRequest::get("/api/tasks/active"))?;
+    assert_eq!(response.status, 200);
+    let body = response.json_body()?;
+    let tasks = body["tasks"].as_array().cloned().unwrap_or_default();
+    assert_eq!(
+        tasks.len(),
+        1,
+        "only running/pending tasks are listed: {body}"
+    );
+    assert_eq!(tasks[0]["task_id"], "active-running");
+    assert_eq!(tasks[0]["kind"], "manual");
+    assert_eq!(tasks[0]["can_stop"], true);
+    assert_eq!(tasks[0]["started_at"], now);
+    let mut units: Vec<String> = tasks[0]["units"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|v| v.as_str().map(str::to_string))
+        .collect();
+    units.sort();
+    assert_eq!(units, vec!["svc-a.service", "svc-b.service"]);
+
+    Ok(())
+}
+
+async fn scenario_unit_history() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let pool = env.connect_db().await?;
+    let now = current_unix_secs() as i64;
+    for (idx, (task_id, kind, units)) in [
+        ("hist-1", "manual", vec!["svc-a.service", "svc-b.service"]),
+        ("hist-2", "github-webhook", vec!["svc-a.service"]),
+        ("hist-3", "scheduler", vec!["svc-b.service"]),
+        ("hist-4", "manual", vec!["svc-a.service"]),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        sqlx::query(
+            "INSERT INTO tasks (task_id, kind, status, created_at, summary, meta, trigger_source) \
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(task_id)
+        .bind(kind)
+        .bind("succeeded")
+        .bind(now + idx as i64)
+        .bind("history task")
+        .bind("{}")
+        .bind("test")
+        .execute(&pool)
+        .await?;
+        for unit in units {
+            sqlx::query(
+                "INSERT INTO task_units (task_id, unit, slug, status, duration_ms) VALUES (?, ?, ?, ?, ?)",
+            )
+            .bind(task_id)
+            .bind(unit)
+            .bind(unit.trim_end_matches(".service"))
+            .bind("succeeded")
+            .bind(1500_i64)
+            .execute(&pool)
+            .await?;
+        }
+    }
+
+    let first = env.send_request(HttpRequest::get(
+        "/api/units/svc-a.service/history?per_page=2",
+    ))?;
+    assert_eq!(first.status, 200);
+    let body = first.json_body()?;
+    assert_eq!(body["total"], 3);
+    assert_eq!(body["has_next"], true);
+    let ids: Vec<&str> = body["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|e| e["task_id"].as_str())
+        .collect();
+    assert_eq!(ids, vec!["hist-4", "hist-2"]);
+    assert_eq!(body["entries"][1]["kind"], "github-webhook");
+    assert_eq!(body["entries"][0]["unit"]["unit"], "svc-a.service");
+    assert_eq!(body["entries"][0]["unit"]["duration_ms"], 1500);
+
+    let second = env.send_request(HttpRequest::get(
+        "/api/units/svc-a/history?per_page=2&page=2",
+    ))?;
+    assert_eq!(second.status, 200);
+    let body = second.json_body()?;
+    assert_eq!(body["has_next"], false);
+    assert_eq!(body["entries"][0]["task_id"], "hist-1");
+
+    let unknown = env.send_request(HttpRequest::get("/api/units/missing.service/history"))?;
+    assert_eq!(unknown.status, 200);
+    assert_eq!(unknown.json_body()?["total"], 0);
+
+    let bad_route = env.send_request(HttpRequest::get("/api/units/svc-a.service"))?;
+    assert_eq!(bad_route.status, 404);
+
+    Ok(())
+}
+
+async fn scenario_task_concurrency_queue() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+
+    let pool = env.connect_db().await?;
+    let now = current_unix_secs() as i64;
+    sqlx::query(
+        "INSERT INTO tasks (task_id, kind, status, created_at, started_at, summary, meta, trigger_source, can_stop) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind("queue-blocker")
+    .bind("manual")
+    .bind("running")
+    .bind(now - 10)
+    .bind(now - 10)
+    .bind("blocker")
+    .bind("{}")
+    .bind("test")
+    .bind(1_i64)
+    .execute(&pool)
+    .await?;
+
+    // The cap is counted per executor: a running local-child task does not
+    // take the systemd-run slot.
+    sqlx::query(
+        "INSERT INTO tasks (task_id, kind, status, created_at, started_at, summary, meta, trigger_source, can_stop) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind("queue-other-executor")
+    .bind("maintenance")
+    .bind("running")
+    .bind(now - 10)
+    .bind(now - 10)
+    .bind("other executor")
+    .bind("{}")
+    .bind("test")
+    .bind(1_i64)
+    .execute(&pool)
+    .await?;
+
+    let limit_env = |cmd: &mut Command| {
+        cmd.env("PODUP_MAX_CONCURRENT_TASKS", "1");
+        cmd.env("PODUP_TASK_EXECUTOR_BY_KIND", "maintenance=local-child");
+    };
+
+    env.clear_mock_log()?;
+    let mut queued_ids = Vec::new();
+    for _ in 0..2 {
+        let response = env.send_request_with_env(
+            HttpRequest::post("/api/manual/trigger")
+                .header("content-type", "application/json")
+                .header("x-podup-csrf", "1")
+                .body(
+                    json!({ "units": ["svc-alpha.service"], "caller": "ci" })
+                        .to_string()
+                        .into_bytes(),
+                ),
+            limit_env,
+        )?;
+        assert_eq!(response.status, 202, "{}", response.body_text());
+        let task_id = response.json_body()?["task_id"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        assert!(!task_id.is_empty());
+        queued_ids.push(task_id);
+    }
+    assert!(
+        env.read_mock_log()?
+            .iter()
+            .all(|line| !line.contains("systemd-run")),
+        "queued tasks must not be dispatched"
+    );
+
+    for (idx, task_id) in queued_ids.iter().enumerate() {
+        let detail = env.send_request(HttpRequest::get(&format!("/api/tasks/{task_id}")))?;
+        assert_eq!(detail.status, 200);
+        let detail = detail.json_body()?;
+        assert_eq!(detail["status"], "queued", "{detail}");
+        assert_eq!(detail["queue_position"], (idx + 1) as u64);
+        assert!(
+            detail["logs"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|log| log["action"] == "task-queued"),
+            "task-queued log recorded: {detail}"
+        );
+    }
+
+    let active = env.send_request(HttpRequest::get("/api/tasks/active"))?;
+    assert_eq!(active.json_body()?["tasks"].as_array().unwrap().len(), 4);
+
+    let stopped = env.send_request(
+        HttpRequest::post(&format!("/api/tasks/{}/stop", queued_ids[1]))
+            .header("x-podup-csrf", "1"),
+    )?;
+    assert_eq!(stopped.status, 200, "{}", stopped.body_text());
+    let stopped = stopped.json_body()?;
+    assert_eq!(stopped["status"], "cancelled");
+    assert!(stopped.get("queue_position").is_none());
+
+    sqlx::query("UPDATE tasks SET status = 'succeeded', finished_at = ? WHERE task_id = ?")
+        .bind(now)
+        .bind("queue-blocker")
+        .execute(&pool)
+        .await?;
+
+    // A slot is free, but an older task is still queued: new tasks line up
+    // behind it instead of overtaking it.
+    let late = env.send_request_with_env(
+        HttpRequest::post("/api/manual/trigger")
+            .header("content-type", "application/json")
+            .header("x-podup-csrf", "1")
+            .body(
+                json!({ "units": ["svc-alpha.service"], "caller": "ci" })
+                    .to_string()
+                    .into_bytes(),
+            ),
+        limit_env,
+    )?;
+    assert_eq!(late.status, 202, "{}", late.body_text());
+    let late_id = late.json_body()?["task_id"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    let late = env.send_request(HttpRequest::get(&format!("/api/tasks/{late_id}")))?;
+    let late = late.json_body()?;
+    assert_eq!(late["status"], "queued", "{late}");
+    assert_eq!(late["queue_position"], 2);
+
+    // Maintenance mode holds the queue back even with a free slot.
+    let mut held_cmd = env.command();
+    held_cmd
+        .arg("run-task")
+        .arg("queue-blocker")
+        .env("PODUP_READ_ONLY", "1");
+    limit_env(&mut held_cmd);
+    let _ = env.run_command(held_cmd)?;
+    let held = env.send_request(HttpRequest::get(&format!("/api/tasks/{}", queued_ids[0])))?;
+    assert_eq!(held.json_body()?["status"], "queued");
+
+    // A finishing run-task worker drains the queue into the freed slot.
+    let mut drain_cmd = env.command();
+    drain_cmd.arg("run-task").arg("queue-blocker");
+    limit_env(&mut drain_cmd);
+    let _ = env.run_command(drain_cmd)?;
+
+    let promoted = env.send_request(HttpRequest::get(&format!("/api/tasks/{}", queued_ids[0])))?;
+    let promoted = promoted.json_body()?;
+    assert_ne!(promoted["status"], "queued", "{promoted}");
+    assert!(promoted.get("queue_position").is_none());
+    assert!(
+        promoted["logs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|log| log["action"] == "task-dequeued"),
+        "task-dequeued log recorded: {promoted}"
     );
-    let body = detail.json_body()?;
-    assert_eq!(body["status"], Value::from("failed"));
-    let logs = body["logs"].as_array().cloned().unwrap_or_default();
     assert!(
-        logs.iter().any(
-            |entry| entry.get("action") == Some(&Value::from("task-dispatch-failed"))
-                && entry.get("status") == Some(&Value::from("failed"))
-        ),
-        "scheduler dispatch failure task should record task-dispatch-failed log entry"
+        env.read_mock_log()?
+            .iter()
+            .any(|line| line.contains("systemd-run") && line.contains(&queued_ids[0])),
+        "promoted task dispatched via systemd-run"
     );
 
     Ok(())
@@ -2597,6 +6484,122 @@ async fn scenario_settings_tasks_retention() -> AnyResult<()> {
         !env_override,
         "env_override should be false when PODUP_TASK_RETENTION_SECS is not set"
     );
+    assert_eq!(
+        json["env"]["PODUP_GITHUB_TOKEN_configured"],
+        Value::from(false)
+    );
+
+    let response = env.send_request_with_env(HttpRequest::get("/api/settings"), |cmd| {
+        cmd.env("PODUP_GITHUB_TOKEN", "ghp_settings_probe");
+    })?;
+    assert_eq!(response.status, 200);
+    assert!(!response.body_text().contains("ghp_settings_probe"));
+    let json = response.json_body()?;
+    assert_eq!(
+        json["env"]["PODUP_GITHUB_TOKEN_configured"],
+        Value::from(true)
+    );
+
+    // The CLI dumps the same snapshot without the HTTP server or admin auth.
+    let mut settings_cmd = env.command();
+    settings_cmd
+        .arg("settings")
+        .arg("--json")
+        .env("PODUP_DEV_OPEN_ADMIN", "0")
+        .env("PODUP_TASK_RETENTION_SECS", "7200");
+    let output = env.run_command(settings_cmd)?;
+    assert!(
+        output.status.success(),
+        "settings --json: {}",
+        output.stderr
+    );
+    let cli: Value = serde_json::from_str(output.stdout.trim())?;
+    assert_eq!(cli["tasks"]["task_retention_secs"], 7200);
+    assert_eq!(cli["tasks"]["env_override"], true);
+    assert_eq!(cli["version"], json["version"]);
+
+    let mut table_cmd = env.command();
+    table_cmd
+        .arg("settings")
+        .arg("--output-format")
+        .arg("table");
+    let output = env.run_command(table_cmd)?;
+    assert!(output.status.success(), "settings table: {}", output.stderr);
+    assert!(
+        output
+            .stdout
+            .lines()
+            .any(|line| line.starts_with("tasks.task_retention_secs") && line.ends_with("86400")),
+        "{}",
+        output.stdout
+    );
+
+    Ok(())
+}
+
+async fn scenario_events_csv_export() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+    let pool = env.connect_db().await?;
+
+    for (idx, meta) in [
+        json!({ "note": "plain" }),
+        json!({ "note": "has \"quotes\", commas\nand newlines" }),
+        json!({ "note": "third" }),
+    ]
+    .iter()
+    .enumerate()
+    {
+        sqlx::query(
+            "INSERT INTO event_log (request_id, ts, method, path, status, action, duration_ms, meta, task_id) \
+             VALUES (?, ?, 'POST', '/api/csv,probe', 200, 'csv-probe', 5, ?, 'csv-task')",
+        )
+        .bind(format!("csv-{idx}"))
+        .bind(1_000 + idx as i64)
+        .bind(meta.to_string())
+        .execute(&pool)
+        .await?;
+    }
+
+    let response = env.send_request(HttpRequest::get(
+        "/api/events/export.csv?action=csv-probe&limit=2",
+    ))?;
+    assert_eq!(response.status, 200);
+    assert!(response.headers["content-type"].starts_with("text/csv"));
+    assert!(response.headers["content-disposition"].starts_with("attachment;"));
+
+    let body = response.body_text();
+    let mut lines = body.split("\r\n");
+    assert_eq!(
+        lines.next(),
+        Some("id,ts,method,path,status,action,duration_ms,task_id,meta")
+    );
+    let rows: Vec<&str> = lines.filter(|line| !line.is_empty()).collect();
+    assert_eq!(rows.len(), 2, "limit caps rows, newest first: {body}");
+    assert!(rows[0].contains(",1002,POST,\"/api/csv,probe\",200,csv-probe,5,csv-task,"));
+    assert!(
+        rows[0].ends_with(r#""{""note"":""third""}""#),
+        "{}",
+        rows[0]
+    );
+    // Quotes inside the JSON are doubled; the newline stays JSON-escaped.
+    assert!(rows[1].contains(r#"""note"":""has \""quotes\"", commas\nand newlines"""#));
+
+    let filtered = env.send_request(HttpRequest::get(
+        "/api/events/export.csv?action=does-not-exist",
+    ))?;
+    assert_eq!(filtered.status, 200);
+    assert_eq!(
+        filtered.body,
+        b"id,ts,method,path,status,action,duration_ms,task_id,meta\r\n"
+    );
+
+    let events = env.fetch_events(&pool).await?;
+    assert!(
+        events
+            .iter()
+            .any(|row| row.action == "events-export-api" && row.meta["rows"] == 2)
+    );
 
     Ok(())
 }
@@ -2628,42 +6631,288 @@ async fn scenario_events_task_filter() -> AnyResult<()> {
         "cli-trigger events should include a task_id in meta"
     );
 
-    let path = format!("/api/events?task_id={task_id}");
-    let response = env.send_request(HttpRequest::get(&path))?;
-    assert_eq!(response.status, 200, "/api/events?task_id status");
-    let body = response.json_body()?;
-    let events = body["events"].as_array().cloned().unwrap_or_default();
+    let path = format!("/api/events?task_id={task_id}");
+    let response = env.send_request(HttpRequest::get(&path))?;
+    assert_eq!(response.status, 200, "/api/events?task_id status");
+    let body = response.json_body()?;
+    let events = body["events"].as_array().cloned().unwrap_or_default();
+    assert!(
+        !events.is_empty(),
+        "/api/events?task_id filter should return at least one event"
+    );
+    assert_eq!(
+        body["links"]["self"],
+        format!("/api/events?task_id={task_id}&page=1&page_size=50")
+    );
+    assert!(body["links"]["prev"].is_null());
+
+    Ok(())
+}
+
+async fn scenario_task_logs_cli() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.clear_mock_log()?;
+
+    let payload = github_registry_payload("koha", "svc-alpha", "main");
+    let signature = env.github_signature(&payload);
+    let request = HttpRequest::post("/github-package-update/svc-alpha")
+        .header("x-github-event", "registry_package")
+        .header("x-github-delivery", "cli-logs")
+        .header("x-hub-signature-256", &signature)
+        .body(payload.clone());
+    let response = env.send_request_with_env(request, |cmd| {
+        cmd.env("MOCK_PODMAN_FAIL", "1");
+    })?;
+    assert_eq!(response.status, 202, "{}", response.body_text());
+
+    let pool = env.connect_db().await?;
+    let events = env.fetch_events(&pool).await?;
+    let task_id = events
+        .iter()
+        .find(|row| row.action == "github-webhook")
+        .and_then(|row| row.meta.get("task_id"))
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+    assert!(
+        !task_id.is_empty(),
+        "github-webhook should record a task_id"
+    );
+
+    let status: String = sqlx::query_scalar("SELECT status FROM tasks WHERE task_id = ?")
+        .bind(&task_id)
+        .fetch_one(&pool)
+        .await?;
+    let error_logs: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM task_logs WHERE task_id = ? AND level = 'error'")
+            .bind(&task_id)
+            .fetch_one(&pool)
+            .await?;
+
+    let mut cmd = env.command();
+    cmd.arg("task-logs").arg(&task_id);
+    let plain = env.run_command(cmd)?;
+    assert!(plain.status.success(), "stderr: {}", plain.stderr);
+    assert!(
+        plain.stdout.contains("image-pull"),
+        "task-logs should print the image-pull log, got: {}",
+        plain.stdout
+    );
+
+    let mut cmd = env.command();
+    cmd.arg("task-logs")
+        .arg(&task_id)
+        .arg("--follow")
+        .arg("--level")
+        .arg("error");
+    let followed = env.run_command(cmd)?;
+    let lines: Vec<&str> = followed.stdout.lines().collect();
+    assert_eq!(
+        lines.len() as i64,
+        error_logs,
+        "stdout: {}",
+        followed.stdout
+    );
+    assert!(lines.iter().all(|line| line.contains(" ERROR ")));
+    let expect_success = matches!(status.as_str(), "succeeded" | "skipped");
+    assert_eq!(
+        followed.status.success(),
+        expect_success,
+        "exit code should reflect task status {status}, stderr: {}",
+        followed.stderr
+    );
+
+    let mut cmd = env.command();
+    cmd.arg("task-logs").arg("missing-task");
+    let missing = env.run_command(cmd)?;
+    assert_eq!(missing.status.code(), Some(1));
+
+    let mut cmd = env.command();
+    cmd.arg("task-logs")
+        .arg(&task_id)
+        .arg("--level")
+        .arg("verbose");
+    let invalid = env.run_command(cmd)?;
+    assert_eq!(invalid.status.code(), Some(2));
+
+    Ok(())
+}
+
+async fn scenario_task_command_logs() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.clear_mock_log()?;
+
+    // Trigger a github webhook that will cause podman pull to fail so we can
+    // assert command-level task logs.
+    let payload = github_registry_payload("koha", "svc-alpha", "main");
+    let signature = env.github_signature(&payload);
+    let request = HttpRequest::post("/github-package-update/svc-alpha")
+        .header("x-github-event", "registry_package")
+        .header("x-github-delivery", "cmd-logs")
+        .header("x-hub-signature-256", &signature)
+        .body(payload.clone());
+
+    let response = env.send_request_with_env(request, |cmd| {
+        cmd.env("MOCK_PODMAN_FAIL", "1");
+    })?;
+    assert_eq!(
+        response.status,
+        202,
+        "github webhook with failing podman should still be accepted: {}",
+        response.body_text()
+    );
+
+    let pool = env.connect_db().await?;
+    let events = env.fetch_events(&pool).await?;
+    let task_id = events
+        .iter()
+        .find(|row| row.action == "github-webhook")
+        .and_then(|row| row.meta.get("task_id"))
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+    assert!(
+        !task_id.is_empty(),
+        "github-webhook events should include a task_id in meta"
+    );
+
+    let path = format!("/api/tasks/{task_id}");
+    let detail_resp = env.send_request(HttpRequest::get(&path))?;
+    assert_eq!(
+        detail_resp.status, 200,
+        "/api/tasks/:id should succeed but got {}",
+        detail_resp.status
+    );
+    let body = detail_resp.json_body()?;
+    let logs = body["logs"].as_array().cloned().unwrap_or_default();
+    assert!(
+        !logs.is_empty(),
+        "task detail logs should not be empty for github webhook task"
+    );
+
+    let image_pull_log = logs
+        .iter()
+        .find(|entry| entry.get("action") == Some(&Value::from("image-pull")))
+        .cloned()
+        .expect("expected at least one image-pull task log entry");
+    let meta = image_pull_log
+        .get("meta")
+        .cloned()
+        .unwrap_or_else(|| json!({}));
+
+    assert_eq!(
+        meta.get("type").and_then(|v| v.as_str()),
+        Some("command"),
+        "image-pull meta.type should be 'command'"
+    );
+
+    let command = meta.get("command").and_then(|v| v.as_str()).unwrap_or("");
+    assert!(
+        command.contains("podman pull"),
+        "image-pull meta.command should contain 'podman pull', got: {command}"
+    );
+
+    let stderr = meta
+        .get("stderr_tail")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    assert!(
+        !stderr.is_empty(),
+        "image-pull meta.stderr should be populated"
+    );
+    assert!(
+        stderr.contains("simulated podman pull failure"),
+        "image-pull meta.stderr should include mock failure message, got: {stderr}"
+    );
+
+    let exit = meta.get("exit").and_then(|v| v.as_str()).unwrap_or("");
+    assert!(
+        !exit.is_empty(),
+        "image-pull meta.exit should be a non-empty string"
+    );
+
+    Ok(())
+}
+
+async fn scenario_sse_token_auth() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+    let pool = env.connect_db().await?;
+    let task_id: String = sqlx::query_scalar("SELECT task_id FROM tasks LIMIT 1")
+        .fetch_one(&pool)
+        .await?;
+
+    let token = "stream-token-0123456789";
+    let configure = |cmd: &mut Command| {
+        cmd.env("PODUP_DEV_OPEN_ADMIN", "0");
+        cmd.env("PODUP_FWD_AUTH_HEADER", "x-test-admin");
+        cmd.env("PODUP_FWD_AUTH_ADMIN_VALUE", "yes");
+        cmd.env("PODUP_SSE_TOKEN", token);
+    };
+    let path = format!("/sse/task-logs?task_id={task_id}");
+
+    let anonymous = env.send_request_with_env(HttpRequest::get(&path), configure)?;
+    assert_eq!(anonymous.status, 401);
+
+    let with_token = env.send_request_with_env(
+        HttpRequest::get(&format!("{path}&token={token}")),
+        configure,
+    )?;
+    assert_eq!(with_token.status, 200, "{}", with_token.body_text());
+    assert!(with_token.body_text().contains("event: end"));
+
+    let wrong =
+        env.send_request_with_env(HttpRequest::get(&format!("{path}&token=nope")), configure)?;
+    assert_eq!(wrong.status, 401);
+
+    // The header path keeps working regardless of the query parameter.
+    let header_ok = env.send_request_with_env(
+        HttpRequest::get(&format!("{path}&token=nope")).header("x-test-admin", "yes"),
+        configure,
+    )?;
+    assert_eq!(header_ok.status, 200);
+
+    // Without PODUP_SSE_TOKEN the parameter is never accepted.
+    let disabled =
+        env.send_request_with_env(HttpRequest::get(&format!("{path}&token={token}")), |cmd| {
+            configure(cmd);
+            cmd.env_remove("PODUP_SSE_TOKEN");
+        })?;
+    assert_eq!(disabled.status, 401);
+
+    let hello = env.send_request_with_env(HttpRequest::get("/sse/hello"), configure)?;
+    assert_eq!(hello.status, 200);
+    let hello_wrong =
+        env.send_request_with_env(HttpRequest::get("/sse/hello?token=nope"), configure)?;
+    assert_eq!(hello_wrong.status, 401);
+
+    let events = env.fetch_events(&pool).await?;
     assert!(
-        !events.is_empty(),
-        "/api/events?task_id filter should return at least one event"
+        events
+            .iter()
+            .filter_map(|row| row.meta.get("query").and_then(|q| q.as_str()))
+            .all(|query| !query.contains(token)),
+        "stream tokens must not be stored in the event log"
     );
 
     Ok(())
 }
 
-async fn scenario_task_command_logs() -> AnyResult<()> {
+async fn scenario_task_logs_ws() -> AnyResult<()> {
     let env = TestEnv::new()?;
     env.clear_mock_log()?;
 
-    // Trigger a github webhook that will cause podman pull to fail so we can
-    // assert command-level task logs.
     let payload = github_registry_payload("koha", "svc-alpha", "main");
     let signature = env.github_signature(&payload);
     let request = HttpRequest::post("/github-package-update/svc-alpha")
         .header("x-github-event", "registry_package")
-        .header("x-github-delivery", "cmd-logs")
+        .header("x-github-delivery", "ws-logs")
         .header("x-hub-signature-256", &signature)
         .body(payload.clone());
-
     let response = env.send_request_with_env(request, |cmd| {
         cmd.env("MOCK_PODMAN_FAIL", "1");
     })?;
-    assert_eq!(
-        response.status,
-        202,
-        "github webhook with failing podman should still be accepted: {}",
-        response.body_text()
-    );
+    assert_eq!(response.status, 202, "{}", response.body_text());
 
     let pool = env.connect_db().await?;
     let events = env.fetch_events(&pool).await?;
@@ -2676,64 +6925,145 @@ async fn scenario_task_command_logs() -> AnyResult<()> {
         .to_string();
     assert!(
         !task_id.is_empty(),
-        "github-webhook events should include a task_id in meta"
+        "github-webhook should record a task_id"
     );
 
-    let path = format!("/api/tasks/{task_id}");
-    let detail_resp = env.send_request(HttpRequest::get(&path))?;
-    assert_eq!(
-        detail_resp.status, 200,
-        "/api/tasks/:id should succeed but got {}",
-        detail_resp.status
-    );
-    let body = detail_resp.json_body()?;
-    let logs = body["logs"].as_array().cloned().unwrap_or_default();
-    assert!(
-        !logs.is_empty(),
-        "task detail logs should not be empty for github webhook task"
-    );
+    let path = format!("/ws/task-logs?task_id={task_id}");
 
-    let image_pull_log = logs
-        .iter()
-        .find(|entry| entry.get("action") == Some(&Value::from("image-pull")))
-        .cloned()
-        .expect("expected at least one image-pull task log entry");
-    let meta = image_pull_log
-        .get("meta")
-        .cloned()
-        .unwrap_or_else(|| json!({}));
+    // A plain GET without the upgrade headers is refused.
+    let plain = env.send_request(HttpRequest::get(&path))?;
+    assert_eq!(plain.status, 426, "{}", plain.body_text());
 
+    let response = env.send_request(
+        HttpRequest::get(&path)
+            .header("Upgrade", "websocket")
+            .header("Connection", "Upgrade")
+            .header("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ==")
+            .header("Sec-WebSocket-Version", "13"),
+    )?;
+    assert_eq!(response.status, 101);
     assert_eq!(
-        meta.get("type").and_then(|v| v.as_str()),
-        Some("command"),
-        "image-pull meta.type should be 'command'"
-    );
+        response
+            .headers
+            .get("sec-websocket-accept")
+            .map(String::as_str),
+        Some("s3pPLMBiTxaQ9kYGzzhZRbK+xOo=")
+    );
+
+    let mut messages: Vec<Value> = Vec::new();
+    let mut close_seen = false;
+    for (opcode, payload) in parse_ws_frames(&response.body) {
+        match opcode {
+            0x1 => messages.push(serde_json::from_slice(&payload)?),
+            0x8 => close_seen = true,
+            _ => {}
+        }
+    }
 
-    let command = meta.get("command").and_then(|v| v.as_str()).unwrap_or("");
+    let logs: Vec<&Value> = messages
+        .iter()
+        .filter(|msg| msg["event"] == "log")
+        .collect();
     assert!(
-        command.contains("podman pull"),
-        "image-pull meta.command should contain 'podman pull', got: {command}"
+        logs.len() >= 2,
+        "websocket stream should carry multiple log frames: {messages:?}"
+    );
+    assert!(logs.iter().all(|msg| msg["id"].is_i64()));
+    assert!(logs.iter().any(|msg| msg["data"]["action"] == "image-pull"));
+    assert_eq!(
+        messages.last().map(|msg| msg["event"].clone()),
+        Some(Value::from("end"))
     );
+    assert!(close_seen, "websocket stream should end with a close frame");
 
-    let stderr = meta.get("stderr").and_then(|v| v.as_str()).unwrap_or("");
-    assert!(
-        !stderr.is_empty(),
-        "image-pull meta.stderr should be populated"
+    let events = env.fetch_events(&pool).await?;
+    let audit = events
+        .iter()
+        .find(|row| row.action == "tasks-ws" && row.status == 101)
+        .expect("tasks-ws audit event");
+    assert_eq!(audit.meta["mode"], "websocket");
+
+    // A client close on a still-running task is echoed with the client's
+    // status code. The close frame rides in the same write as the handshake,
+    // so it sits in the server's request buffer when the reader starts.
+    let now = current_unix_secs() as i64;
+    sqlx::query(
+        "INSERT INTO tasks (task_id, kind, status, created_at, started_at, finished_at, summary, meta, trigger_source) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind("ws-running")
+    .bind("manual")
+    .bind("running")
+    .bind(now)
+    .bind(now)
+    .bind(Option::<i64>::None)
+    .bind("running task")
+    .bind("{}")
+    .bind("test")
+    .execute(&pool)
+    .await?;
+
+    let mut raw = HttpRequest::get("/ws/task-logs?task_id=ws-running")
+        .header("Upgrade", "websocket")
+        .header("Connection", "Upgrade")
+        .header("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ==")
+        .header("Sec-WebSocket-Version", "13")
+        .into_bytes();
+    let mask = [7_u8, 1, 9, 3];
+    let code = 1001_u16.to_be_bytes();
+    raw.extend_from_slice(&[0x88, 0x80 | code.len() as u8]);
+    raw.extend_from_slice(&mask);
+    raw.extend(code.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+
+    let response = env.send_bytes_with_env(raw, |cmd| {
+        cmd.env("PODUP_SSE_HEARTBEAT_SECS", "1");
+    })?;
+    assert_eq!(response.status, 101);
+    let frames = parse_ws_frames(&response.body);
+    let closes: Vec<&Vec<u8>> = frames
+        .iter()
+        .filter(|(opcode, _)| *opcode == 0x8)
+        .map(|(_, payload)| payload)
+        .collect();
+    assert_eq!(closes.len(), 1, "exactly one close frame: {frames:?}");
+    assert_eq!(
+        closes[0].as_slice(),
+        &code[..],
+        "close echoes the client code"
     );
-    assert!(
-        stderr.contains("simulated podman pull failure"),
-        "image-pull meta.stderr should include mock failure message, got: {stderr}"
+    assert_eq!(
+        frames.last().map(|(opcode, _)| *opcode),
+        Some(0x8),
+        "no frames follow the close"
     );
 
-    let exit = meta.get("exit").and_then(|v| v.as_str()).unwrap_or("");
-    assert!(
-        !exit.is_empty(),
-        "image-pull meta.exit should be a non-empty string"
-    );
+    let events = env.fetch_events(&pool).await?;
+    let audit = events
+        .iter()
+        .find(|row| row.action == "tasks-ws" && row.meta["task_id"] == "ws-running")
+        .expect("tasks-ws audit event for the running task");
+    assert_eq!(audit.meta["reason"], "client-disconnect");
 
     Ok(())
 }
 
+/// Splits a server-to-client WebSocket stream into `(opcode, payload)` pairs.
+/// Server frames are unmasked and small enough here for 7/16-bit lengths.
+fn parse_ws_frames(mut rest: &[u8]) -> Vec<(u8, Vec<u8>)> {
+    let mut frames = Vec::new();
+    while rest.len() >= 2 {
+        let opcode = rest[0] & 0x0F;
+        let (len, header_len) = match rest[1] & 0x7F {
+            126 => (u16::from_be_bytes([rest[2], rest[3]]) as usize, 4),
+            127 => panic!("unexpected 64-bit frame in task log stream"),
+            len => (len as usize, 2),
+        };
+        frames.push((opcode, rest[header_len..header_len + len].to_vec()));
+        rest = &rest[header_len + len..];
+    }
+    frames
+}
+
 async fn scenario_task_logs_sse() -> AnyResult<()> {
     let env = TestEnv::new()?;
     env.clear_mock_log()?;
@@ -2795,6 +7125,31 @@ async fn scenario_task_logs_sse() -> AnyResult<()> {
         "SSE log data should contain JSON with a command field, got: {body}"
     );
 
+    let ids: Vec<i64> = body
+        .lines()
+        .filter_map(|line| line.strip_prefix("id: "))
+        .filter_map(|id| id.trim().parse().ok())
+        .collect();
+    assert_eq!(
+        ids.len(),
+        log_event_count,
+        "every SSE log frame should carry an id, got: {body}"
+    );
+
+    // Reconnecting with Last-Event-Id resumes after the given log.
+    let resume_from = ids[0];
+    let resumed = env
+        .send_request(HttpRequest::get(&path).header("Last-Event-Id", &resume_from.to_string()))?;
+    assert_eq!(resumed.status, 200);
+    let resumed_body = resumed.body_text();
+    let resumed_ids: Vec<i64> = resumed_body
+        .lines()
+        .filter_map(|line| line.strip_prefix("id: "))
+        .filter_map(|id| id.trim().parse().ok())
+        .collect();
+    assert_eq!(resumed_ids, ids[1..].to_vec());
+    assert!(resumed_body.contains("event: end"));
+
     Ok(())
 }
 
@@ -2863,6 +7218,78 @@ async fn scenario_error_paths() -> AnyResult<()> {
             .iter()
             .any(|row| row.action == "github-webhook" && row.status == 401)
     );
+    let failure = events
+        .iter()
+        .find(|row| row.action == "webhook-signature-failed")
+        .expect("webhook-signature-failed event");
+    assert_eq!(failure.status, 401);
+    assert_eq!(failure.meta["reason"], "signature-mismatch");
+    assert_eq!(failure.meta["delivery"], "invalid-signature");
+    assert_eq!(failure.meta["unit"], "svc-alpha.service");
+    assert!(
+        !failure.meta.to_string().contains("deadbeef"),
+        "signature must not be stored: {}",
+        failure.meta
+    );
+
+    Ok(())
+}
+
+async fn scenario_generic_webhook_signature() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.clear_mock_log()?;
+
+    let payload = github_registry_payload("koha", "svc-alpha", "main");
+    let mut mac = Hmac::<sha1::Sha1>::new_from_slice(env.github_secret.as_bytes()).unwrap();
+    mac.update(&payload);
+    let signature = format!("sha1={:x}", mac.finalize().into_bytes());
+
+    let configure = |cmd: &mut Command| {
+        cmd.env("PODUP_WEBHOOK_SIG_HEADER", "X-CI-Signature");
+        cmd.env("PODUP_WEBHOOK_SIG_ALGO", "sha1");
+        configure_image_verify_mocks(cmd);
+    };
+
+    let response = env.send_request_with_env(
+        HttpRequest::post("/webhook-package-update/svc-alpha")
+            .header("x-ci-signature", &signature)
+            .header("x-github-delivery", "generic-1")
+            .body(payload.clone()),
+        configure,
+    )?;
+    assert_eq!(
+        response.status,
+        202,
+        "generic webhook accepted: {}",
+        response.body_text()
+    );
+
+    // The GitHub-style sha256 signature is not accepted once sha1 is configured.
+    let rejected = env.send_request_with_env(
+        HttpRequest::post("/webhook-package-update/svc-alpha")
+            .header("x-ci-signature", &env.github_signature(&payload))
+            .body(payload.clone()),
+        configure,
+    )?;
+    assert_eq!(rejected.status, 401);
+
+    let missing = env.send_request_with_env(
+        HttpRequest::post("/webhook-package-update/svc-alpha")
+            .header("x-hub-signature-256", &env.github_signature(&payload))
+            .body(payload.clone()),
+        configure,
+    )?;
+    assert_eq!(missing.status, 401);
+
+    let mut cmd = env.command();
+    cmd.arg("http-server").env("PODUP_WEBHOOK_SIG_ALGO", "md5");
+    let result = env.run_command(cmd)?;
+    assert!(!result.status.success());
+    assert!(
+        result.stderr.contains("PODUP_WEBHOOK_SIG_ALGO"),
+        "startup error should name the variable: {}",
+        result.stderr
+    );
 
     Ok(())
 }
@@ -2881,6 +7308,264 @@ async fn scenario_static_assets() -> AnyResult<()> {
     assert_eq!(asset.status, 200);
     assert!(String::from_utf8_lossy(&asset.body).contains("window.__E2E__"));
 
+    let index_etag = index.headers.get("etag").cloned().expect("index etag");
+    assert!(index_etag.starts_with('"') && index_etag.ends_with('"'));
+    assert_eq!(
+        index.headers.get("cache-control").map(String::as_str),
+        Some("public, max-age=60, must-revalidate")
+    );
+    let asset_etag = asset.headers.get("etag").cloned().expect("asset etag");
+    assert_ne!(index_etag, asset_etag);
+
+    let revalidated =
+        env.send_request(HttpRequest::get("/").header("If-None-Match", &index_etag))?;
+    assert_eq!(revalidated.status, 304);
+    assert!(revalidated.body.is_empty());
+    assert_eq!(revalidated.headers.get("etag"), Some(&index_etag));
+
+    let weak = env.send_request(
+        HttpRequest::new("HEAD", "/assets/app.js")
+            .header("If-None-Match", &format!("\"stale\", W/{asset_etag}")),
+    )?;
+    assert_eq!(weak.status, 304);
+
+    let stale = env.send_request(HttpRequest::get("/").header("If-None-Match", "\"stale\""))?;
+    assert_eq!(stale.status, 200);
+    assert!(String::from_utf8_lossy(&stale.body).contains("Hello from e2e dist"));
+
+    // Large binary assets are streamed from disk with a metadata length and
+    // the same content-hash ETag as buffered ones.
+    let video: Vec<u8> = (0..3 * 1024 * 1024 + 17).map(|i| (i % 251) as u8).collect();
+    fs::write(
+        env.state_dir.join("web/dist/assets/intro-Z9x8Y7w6.mp4"),
+        &video,
+    )?;
+    let streamed = env.send_request(HttpRequest::get("/assets/intro-Z9x8Y7w6.mp4"))?;
+    assert_eq!(streamed.status, 200);
+    assert_eq!(
+        streamed.headers.get("content-length"),
+        Some(&video.len().to_string())
+    );
+    assert!(streamed.body == video, "streamed body must match the file");
+    let video_etag = streamed.headers.get("etag").cloned().expect("video etag");
+    let head = env.send_request(
+        HttpRequest::new("HEAD", "/assets/intro-Z9x8Y7w6.mp4").header("If-None-Match", &video_etag),
+    )?;
+    assert_eq!(head.status, 304);
+
+    // Client-side deep links fall back to index.html; API, stream, asset,
+    // webhook and file-like paths still 404.
+    let deep_link = env.send_request(HttpRequest::get("/tasks/tsk_123"))?;
+    assert_eq!(deep_link.status, 200);
+    assert!(String::from_utf8_lossy(&deep_link.body).contains("Hello from e2e dist"));
+    for path in [
+        "/api/unknown",
+        "/sse/unknown",
+        "/ws/unknown",
+        "/assets/missing.js",
+        "/hook",
+        "/robots.txt",
+    ] {
+        let missing = env.send_request(HttpRequest::get(path))?;
+        assert_eq!(missing.status, 404, "path={path}");
+    }
+    let posted = env.send_request(HttpRequest::post("/tasks/tsk_123"))?;
+    assert_eq!(posted.status, 404);
+    let disabled = env.send_request_with_env(HttpRequest::get("/tasks/tsk_123"), |cmd| {
+        cmd.env("PODUP_SPA_FALLBACK", "0");
+    })?;
+    assert_eq!(disabled.status, 404);
+
+    let pool = env.connect_db().await?;
+    let events = env.fetch_events(&pool).await?;
+    assert!(
+        events.iter().any(|row| row.action == "frontend"
+            && row.meta["streamed"] == Value::Bool(true)
+            && row.meta["response_size"] == video.len() as u64),
+        "streamed asset should be audited"
+    );
+    assert!(events.iter().any(|row| row.action == "frontend"
+        && row.status == 200
+        && row.meta["spa_fallback"] == Value::Bool(true)));
+
+    Ok(())
+}
+
+async fn scenario_base_path() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+    env.ensure_db_initialized().await?;
+    let with_base = |cmd: &mut Command| {
+        cmd.env("PODUP_BASE_PATH", "/podup/");
+    };
+
+    let index = env.send_request_with_env(HttpRequest::get("/podup/tasks"), with_base)?;
+    assert_eq!(index.status, 200);
+    let html = String::from_utf8_lossy(&index.body);
+    assert!(html.contains("Hello from e2e dist"), "{html}");
+    assert!(html.contains("<base href=\"/podup/\" />"), "{html}");
+    assert!(
+        html.contains("window.__PODUP_BASE_PATH__=\"/podup\""),
+        "{html}"
+    );
+
+    let asset = env.send_request_with_env(HttpRequest::get("/podup/assets/app.js"), with_base)?;
+    assert_eq!(asset.status, 200);
+    assert!(String::from_utf8_lossy(&asset.body).contains("window.__E2E__"));
+
+    let health = env.send_request_with_env(HttpRequest::get("/podup/health"), with_base)?;
+    assert_eq!(health.status, 200, "{}", health.body_text());
+
+    let settings = env.send_request_with_env(HttpRequest::get("/podup/api/settings"), with_base)?;
+    assert_eq!(settings.status, 200, "{}", settings.body_text());
+
+    // CSRF enforcement still applies to prefixed side-effect routes.
+    let denied =
+        env.send_request_with_env(HttpRequest::post("/podup/api/tasks/stop-all"), with_base)?;
+    assert_eq!(denied.status, 403, "{}", denied.body_text());
+    let allowed = env.send_request_with_env(
+        HttpRequest::post("/podup/api/tasks/stop-all").header("x-podup-csrf", "1"),
+        with_base,
+    )?;
+    assert_eq!(allowed.status, 200, "{}", allowed.body_text());
+
+    // Requests the proxy already stripped keep routing.
+    let stripped = env.send_request_with_env(HttpRequest::get("/api/settings"), with_base)?;
+    assert_eq!(stripped.status, 200);
+
+    let webhooks = env.send_request_with_env(HttpRequest::get("/api/webhooks/status"), |cmd| {
+        cmd.env("PODUP_BASE_PATH", "/podup");
+        cmd.env("PODUP_PUBLIC_BASE_URL", "https://ci.example.com");
+    })?;
+    assert_eq!(webhooks.status, 200);
+    let json = webhooks.json_body()?;
+    let units = json["units"].as_array().expect("webhook units");
+    assert!(!units.is_empty(), "{json}");
+    assert!(
+        units
+            .iter()
+            .all(|unit| unit["webhook_url"].as_str().is_some_and(
+                |url| url.starts_with("https://ci.example.com/podup/github-package-update/")
+            )),
+        "{json}"
+    );
+
+    Ok(())
+}
+
+async fn scenario_method_not_allowed() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+
+    let cases = [
+        (HttpRequest::new("DELETE", "/api/tasks"), "GET, POST"),
+        (HttpRequest::get("/api/tasks/tsk_demo/stop"), "POST"),
+        (HttpRequest::post("/api/settings"), "GET"),
+        (HttpRequest::post("/sse/task-logs"), "GET"),
+        (HttpRequest::get("/api/manual/trigger"), "POST"),
+        (HttpRequest::post("/api/manual/services"), "GET"),
+        (HttpRequest::post("/last_payload.bin"), "GET, HEAD"),
+    ];
+
+    for (request, expected) in cases {
+        let path = request.path.clone();
+        let response = env.send_request(request)?;
+        assert_eq!(response.status, 405, "path={path}");
+        assert_eq!(
+            response.headers.get("allow").map(String::as_str),
+            Some(expected),
+            "path={path}"
+        );
+    }
+
+    Ok(())
+}
+
+async fn scenario_gzip_responses() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+
+    let plain = env.send_request_with_env(HttpRequest::get("/api/settings"), |cmd| {
+        cmd.env("PODUP_GZIP_MIN_BYTES", "1");
+    })?;
+    assert_eq!(plain.status, 200);
+    assert!(!plain.headers.contains_key("content-encoding"));
+    let plain_json: Value = serde_json::from_slice(&plain.body)?;
+
+    let gzipped = env.send_request_with_env(
+        HttpRequest::get("/api/settings").header("Accept-Encoding", "br, gzip;q=0.8"),
+        |cmd| {
+            cmd.env("PODUP_GZIP_MIN_BYTES", "1");
+        },
+    )?;
+    assert_eq!(gzipped.status, 200);
+    assert_eq!(
+        gzipped.headers.get("content-encoding").map(String::as_str),
+        Some("gzip")
+    );
+    assert_eq!(
+        gzipped.headers.get("content-length").map(String::as_str),
+        Some(gzipped.body.len().to_string().as_str())
+    );
+    let mut decoded = Vec::new();
+    GzDecoder::new(gzipped.body.as_slice()).read_to_end(&mut decoded)?;
+    let decoded_json: Value = serde_json::from_slice(&decoded)?;
+    assert_eq!(decoded_json["scheduler"], plain_json["scheduler"]);
+
+    // Below the threshold responses stay uncompressed.
+    let small = env.send_request_with_env(
+        HttpRequest::get("/api/settings").header("Accept-Encoding", "gzip"),
+        |cmd| {
+            cmd.env("PODUP_GZIP_MIN_BYTES", "100000000");
+        },
+    )?;
+    assert!(!small.headers.contains_key("content-encoding"));
+
+    let asset = env.send_request_with_env(
+        HttpRequest::get("/assets/app.js").header("Accept-Encoding", "gzip"),
+        |cmd| {
+            cmd.env("PODUP_GZIP_MIN_BYTES", "1");
+        },
+    )?;
+    assert_eq!(asset.status, 200);
+    assert_eq!(
+        asset.headers.get("content-encoding").map(String::as_str),
+        Some("gzip")
+    );
+    let mut asset_body = String::new();
+    GzDecoder::new(asset.body.as_slice()).read_to_string(&mut asset_body)?;
+    assert!(asset_body.contains("window.__E2E__"));
+
+    // The gzip body has its own validator, distinct from the identity one.
+    let identity = env.send_request(HttpRequest::get("/assets/app.js"))?;
+    let identity_etag = identity
+        .headers
+        .get("etag")
+        .cloned()
+        .expect("identity etag");
+    let gzip_etag = asset.headers.get("etag").cloned().expect("gzip etag");
+    assert_ne!(identity_etag, gzip_etag);
+    assert!(gzip_etag.ends_with("-gzip\""), "{gzip_etag}");
+    let revalidate = |etag: &str| {
+        env.send_request_with_env(
+            HttpRequest::get("/assets/app.js")
+                .header("Accept-Encoding", "gzip")
+                .header("If-None-Match", etag),
+            |cmd| {
+                cmd.env("PODUP_GZIP_MIN_BYTES", "1");
+            },
+        )
+    };
+    let gzip_revalidated = revalidate(&gzip_etag)?;
+    assert_eq!(gzip_revalidated.status, 304);
+    assert_eq!(gzip_revalidated.headers.get("etag"), Some(&gzip_etag));
+    assert_eq!(revalidate(&identity_etag)?.status, 200);
+
+    let sse = env.send_request_with_env(
+        HttpRequest::get("/sse/hello").header("Accept-Encoding", "gzip"),
+        |cmd| {
+            cmd.env("PODUP_GZIP_MIN_BYTES", "1");
+        },
+    )?;
+    assert!(!sse.headers.contains_key("content-encoding"));
+
     Ok(())
 }
 
@@ -2970,6 +7655,42 @@ async fn scenario_cli_maintenance() -> AnyResult<()> {
         prune_output.stderr
     );
 
+    let mut json_trigger = env.command();
+    json_trigger
+        .arg("trigger-units")
+        .arg("svc-alpha.service")
+        .arg("svc-beta.service")
+        .arg("--dry-run")
+        .arg("--json");
+    let json_trigger_output = env.run_command(json_trigger)?;
+    assert!(json_trigger_output.status.success());
+    let report: Value = serde_json::from_str(json_trigger_output.stdout.trim()).map_err(|e| {
+        format!(
+            "trigger --json stdout not JSON: {e}: {}",
+            json_trigger_output.stdout
+        )
+    })?;
+    assert_eq!(report["ok"], true);
+    assert_eq!(report["dry_run"], true);
+    assert_eq!(report["total"], 2);
+    assert_eq!(report["counts"]["dry-run"], 2);
+    assert_eq!(report["results"][0]["unit"], "svc-alpha.service");
+
+    let mut json_prune = env.command();
+    json_prune.arg("prune-state").arg("--dry-run").arg("--json");
+    let json_prune_output = env.run_command(json_prune)?;
+    assert!(json_prune_output.status.success());
+    let report: Value = serde_json::from_str(json_prune_output.stdout.trim()).map_err(|e| {
+        format!(
+            "prune --json stdout not JSON: {e}: {}",
+            json_prune_output.stdout
+        )
+    })?;
+    assert_eq!(report["ok"], true);
+    assert_eq!(report["dry_run"], true);
+    assert!(report["task_id"].is_string());
+    assert!(report["tasks_removed"].is_u64());
+
     let pool = env.connect_db().await?;
     let cli_events: Vec<_> = env
         .fetch_events(&pool)
@@ -2982,6 +7703,43 @@ async fn scenario_cli_maintenance() -> AnyResult<()> {
     Ok(())
 }
 
+async fn scenario_request_timeout() -> AnyResult<()> {
+    let env = TestEnv::new()?;
+
+    let mut cmd = env.command();
+    cmd.arg("server")
+        .env("PODUP_REQUEST_TIMEOUT_SECS", "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = cmd.spawn()?;
+    // Keep the write side open so the server sees a stalled client rather
+    // than EOF.
+    let mut stdin = child.stdin.take().expect("stdin available");
+    stdin.write_all(b"POST /api/manual/trigger HTTP/1.1\r\nHost: localhost\r\nContent-Le")?;
+    stdin.flush()?;
+
+    let started = Instant::now();
+    let output = child.wait_with_output()?;
+    drop(stdin);
+    assert!(output.status.success());
+    assert!(started.elapsed() < Duration::from_secs(10));
+
+    let response = HttpResponse::parse(&output.stdout)?;
+    assert_eq!(response.status, 408, "{}", response.body_text());
+
+    let pool = env.connect_db().await?;
+    let events = env.fetch_events(&pool).await?;
+    assert!(
+        events
+            .iter()
+            .any(|row| row.action == "request-timeout" && row.status == 408),
+        "expected request-timeout event"
+    );
+
+    Ok(())
+}
+
 async fn scenario_http_server() -> AnyResult<()> {
     let env = TestEnv::new()?;
     env.ensure_db_initialized().await?;
@@ -2992,10 +7750,15 @@ async fn scenario_http_server() -> AnyResult<()> {
         drop(listener);
         addr.to_string()
     };
+    let socket_path = env.state_dir.join("podup.sock");
 
     let mut cmd = env.command();
     cmd.arg("http-server");
-    cmd.env("PODUP_HTTP_ADDR", &addr);
+    cmd.env(
+        "PODUP_HTTP_ADDR",
+        format!("{addr},unix:{}", socket_path.display()),
+    );
+    cmd.env("PODUP_TRUSTED_PROXY", "127.0.0.1");
     cmd.stdout(Stdio::null());
     cmd.stderr(Stdio::null());
     let mut child = cmd.spawn()?;
@@ -3005,7 +7768,9 @@ async fn scenario_http_server() -> AnyResult<()> {
     for _ in 0..20 {
         match TcpStream::connect(&addr) {
             Ok(mut stream) => {
-                let request = HttpRequest::get("/health").into_bytes();
+                let request = HttpRequest::get("/health")
+                    .header("X-Forwarded-For", "198.51.100.7, 203.0.113.7")
+                    .into_bytes();
                 stream.write_all(&request)?;
                 // Signal end of request body.
                 let _ = stream.shutdown(std::net::Shutdown::Write);
@@ -3020,8 +7785,27 @@ async fn scenario_http_server() -> AnyResult<()> {
                     response.body_text()
                 );
 
+                // The same server also listens on the unix socket.
+                let mut unix = std::os::unix::net::UnixStream::connect(&socket_path)?;
+                unix.write_all(&HttpRequest::get("/health").into_bytes())?;
+                let _ = unix.shutdown(std::net::Shutdown::Write);
+                let mut buf = Vec::new();
+                unix.read_to_end(&mut buf)?;
+                let response = HttpResponse::parse(&buf)?;
+                assert_eq!(response.status, 200, "http-server /health over unix socket");
+
                 child.kill().ok();
                 child.wait().ok();
+
+                // The peer (127.0.0.1) is a trusted proxy, so the right-most
+                // forwarded hop is recorded as the client.
+                let pool = env.connect_db().await?;
+                let events = env.fetch_events(&pool).await?;
+                let health = events
+                    .iter()
+                    .find(|row| row.action == "health-check" && row.meta.get("client_ip").is_some())
+                    .expect("health-check event with client_ip");
+                assert_eq!(health.meta["client_ip"], "203.0.113.7");
                 return Ok(());
             }
             Err(err) => {
@@ -3239,6 +8023,15 @@ impl TestEnv {
         request: HttpRequest,
         configure: F,
     ) -> AnyResult<HttpResponse>
+    where
+        F: FnOnce(&mut Command),
+    {
+        self.send_bytes_with_env(request.into_bytes(), configure)
+    }
+
+    /// Writes raw bytes to a `server` child, e.g. a request followed by
+    /// WebSocket frames in the same write.
+    fn send_bytes_with_env<F>(&self, payload: Vec<u8>, configure: F) -> AnyResult<HttpResponse>
     where
         F: FnOnce(&mut Command),
     {
@@ -3251,7 +8044,7 @@ impl TestEnv {
         let mut child = cmd.spawn()?;
         {
             let mut stdin = child.stdin.take().expect("stdin available");
-            stdin.write_all(&request.into_bytes())?;
+            stdin.write_all(&payload)?;
         }
         let output = child.wait_with_output()?;
         if !output.status.success() {
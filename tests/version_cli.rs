@@ -41,3 +41,29 @@ fn version_subcommand_outputs_current_release_tag() {
     let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
     assert_eq!(stdout, expected_tag());
 }
+
+#[test]
+fn version_json_outputs_build_metadata() {
+    let exe = env!("CARGO_BIN_EXE_pod-upgrade-trigger");
+    let output = Command::new(exe)
+        .args(["version", "--json"])
+        .output()
+        .expect("failed to run pod-upgrade-trigger version --json");
+
+    assert!(output.status.success(), "exit code should be 0");
+    let payload: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("version --json emits JSON");
+    assert_eq!(payload["release_tag"], expected_tag());
+    assert!(payload["package"].is_string());
+    assert!(payload.get("build_timestamp").is_some());
+    assert!(payload.get("build_tag").is_some());
+
+    let table = Command::new(exe)
+        .args(["version", "--output-format", "table"])
+        .output()
+        .expect("failed to run pod-upgrade-trigger version --output-format table");
+    assert_eq!(
+        String::from_utf8_lossy(&table.stdout).trim(),
+        expected_tag()
+    );
+}
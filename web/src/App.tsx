@@ -9,6 +9,7 @@ import {
 	useLocation,
 	useNavigate,
 } from "react-router-dom";
+import { BASE_PATH } from "./basePath";
 import { ToastProvider, ToastViewport, useToast } from "./components/Toast";
 import { ApiProvider, useApi } from "./hooks/useApi";
 import { useVersionCheck } from "./hooks/useVersionCheck";
@@ -209,6 +210,12 @@ export function TopStatusBar() {
 					<p className="mt-2 text-sm text-base-content/70">
 						提示：是否 dry-run 由服务端环境变量决定（UI 不强制真实更新）。
 					</p>
+					{version.majorJump ? (
+						<p className="mt-2 text-sm text-warning">
+							注意：该版本跨越主版本号，可能需要手动迁移步骤。
+							{version.warning ? ` ${version.warning}` : null}
+						</p>
+					) : null}
 					<div className="modal-action">
 						<form method="dialog">
 							<button
@@ -353,7 +360,7 @@ type AppProps = {
 
 export default function App({ mockEnabled = false }: AppProps) {
 	return (
-		<BrowserRouter>
+		<BrowserRouter basename={BASE_PATH || undefined}>
 			<ToastProvider>
 				<ApiProvider>
 					<Layout />
@@ -0,0 +1,34 @@
+declare global {
+	interface Window {
+		__PODUP_BASE_PATH__?: string;
+	}
+}
+
+function normalize(raw: string | undefined): string {
+	const trimmed = (raw ?? "").trim().replace(/^\/+|\/+$/g, "");
+	return trimmed ? `/${trimmed}` : "";
+}
+
+// Injected into index.html by the backend when PODUP_BASE_PATH is set, e.g.
+// "/podup". Empty when the UI is served from the root.
+export const BASE_PATH = normalize(
+	typeof window === "undefined" ? undefined : window.__PODUP_BASE_PATH__,
+);
+
+// Prefix a root-relative backend path ("/api/...", "/sse/...") with the base
+// path. Absolute URLs and already-prefixed paths are returned unchanged.
+export function withBase(path: string): string {
+	if (!BASE_PATH || !path.startsWith("/") || path.startsWith("//")) {
+		return path;
+	}
+	if (path === BASE_PATH || path.startsWith(`${BASE_PATH}/`)) {
+		return path;
+	}
+	return `${BASE_PATH}${path}`;
+}
+
+export function resolveRequestInput(
+	input: RequestInfo | URL,
+): RequestInfo | URL {
+	return typeof input === "string" ? withBase(input) : input;
+}
@@ -9,6 +9,8 @@ export type ManualServiceUpdate = {
 	checked_at?: number;
 	stale?: boolean;
 	reason?: string;
+	error?: string | null;
+	retriable?: boolean | null;
 };
 
 export function ManualUpdateBadge({
@@ -44,9 +46,14 @@ export function ManualUpdateBadge({
 		);
 	}
 
+	const reason = update.reason || "未知原因";
+	const tip = update.error
+		? `${reason} (${update.error})${update.retriable ? "，稍后可重试" : ""}`
+		: reason;
+
 	return (
 		<div className="flex items-center gap-1">
-			<div className="tooltip" data-tip={update.reason || "未知原因"}>
+			<div className="tooltip" data-tip={tip}>
 				<span className="badge badge-ghost badge-sm border-base-content/20 text-base-content/50">
 					未知
 				</span>
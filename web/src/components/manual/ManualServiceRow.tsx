@@ -10,7 +10,10 @@ export type ManualServiceRowService = {
 	slug: string;
 	unit: string;
 	display_name: string;
+	group?: string | null;
+	icon?: string | null;
 	default_image?: string | null;
+	image_source?: "override" | "unit-file" | null;
 	github_path?: string;
 	is_auto_update?: boolean;
 	update?: ManualServiceUpdate | null;
@@ -89,8 +92,16 @@ export function ManualServiceRow({
 		>
 			<div className="flex min-w-0 flex-1 flex-col gap-1">
 				<div className="flex items-center gap-2">
+					{service.icon ? (
+						<Icon icon={service.icon} className="text-base" />
+					) : null}
 					<span className="font-semibold">{service.display_name}</span>
 					<span className="badge badge-ghost badge-xs">{service.unit}</span>
+					{service.group ? (
+						<span className="badge badge-outline badge-xs">
+							{service.group}
+						</span>
+					) : null}
 					{!defaultImage ? (
 						<span className="badge badge-warning badge-xs">缺少镜像</span>
 					) : null}
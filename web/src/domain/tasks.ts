@@ -1,5 +1,10 @@
 export type TaskStatus =
 	| "pending"
+	/**
+	 * Waiting for a free slot under PODUP_MAX_CONCURRENT_TASKS; promoted to
+	 * "running" once dispatched.
+	 */
+	| "queued"
 	| "running"
 	| "succeeded"
 	| "failed"
@@ -147,6 +152,10 @@ export type Task = {
 	 * Number of warning/error-level log entries, when known.
 	 */
 	warning_count?: number | null;
+	/**
+	 * Free-form grouping tags (`prod`, `env=staging`); omitted when empty.
+	 */
+	tags?: string[];
 	/**
 	 * Whether this task is expected to take noticeable time and thus
 	 * should default to drawer auto-open behaviour.
@@ -197,6 +206,9 @@ export type CommandMeta = {
 	type?: string;
 	command?: string;
 	argv?: string[];
+	stdout_tail?: string;
+	stderr_tail?: string;
+	/** Legacy field names written by older backends. */
 	stdout?: string;
 	stderr?: string;
 	exit?: string;
@@ -239,20 +251,64 @@ export function isCommandMeta(meta: unknown): meta is CommandMeta {
 	const hasCommand =
 		typeof candidate.command === "string" &&
 		candidate.command.trim().length > 0;
-	const hasStdout =
-		typeof candidate.stdout === "string" && candidate.stdout.trim().length > 0;
-	const hasStderr =
-		typeof candidate.stderr === "string" && candidate.stderr.trim().length > 0;
+	const hasStdout = nonEmptyString(candidate.stdout_tail ?? candidate.stdout);
+	const hasStderr = nonEmptyString(candidate.stderr_tail ?? candidate.stderr);
 
 	return hasCommandType || hasCommand || hasStdout || hasStderr;
 }
 
+function nonEmptyString(value: unknown): boolean {
+	return typeof value === "string" && value.trim().length > 0;
+}
+
+/**
+ * Returns the captured stdout/stderr of a command log, preferring the
+ * separated `*_tail` fields and falling back to the legacy `stdout`/`stderr`.
+ */
+export function commandOutput(meta: CommandMeta): {
+	stdout: string;
+	stderr: string;
+} {
+	return {
+		stdout: meta.stdout_tail ?? meta.stdout ?? "",
+		stderr: meta.stderr_tail ?? meta.stderr ?? "",
+	};
+}
+
+export type PaginationLinks = {
+	self: string;
+	first: string;
+	prev: string | null;
+	next: string | null;
+};
+
 export type TasksListResponse = {
 	tasks: Task[];
 	total: number;
 	page: number;
 	page_size: number;
 	has_next: boolean;
+	links?: PaginationLinks;
+};
+
+export type TaskKindStats = {
+	total: number;
+	by_status: Partial<Record<TaskStatus, number>>;
+};
+
+export type TaskStatsResponse = {
+	window_hours: number;
+	since: number;
+	total: number;
+	/** succeeded / (succeeded + failed); null when neither occurred. */
+	success_rate: number | null;
+	by_status: Partial<Record<TaskStatus, number>>;
+	by_kind: Partial<Record<TaskKind, TaskKindStats>>;
+	durations: {
+		completed: number;
+		avg_secs: number | null;
+		p95_secs: number | null;
+	};
 };
 
 export type TaskEventsHint = {
@@ -262,4 +318,8 @@ export type TaskEventsHint = {
 export type TaskDetailResponse = Task & {
 	logs: TaskLogEntry[];
 	events_hint?: TaskEventsHint | null;
+	/** 1-based position in the concurrency queue; only set while queued. */
+	queue_position?: number | null;
+	/** systemd-run properties the task runner was started with. */
+	unit_properties?: string[];
 };
@@ -9,6 +9,7 @@ import {
 	useState,
 } from "react";
 import { useLocation, useNavigate } from "react-router-dom";
+import { resolveRequestInput, withBase } from "../basePath";
 import { useToast } from "../components/Toast";
 
 type StreamStatus = "idle" | "connecting" | "open" | "error";
@@ -22,6 +23,8 @@ declare global {
 export type ApiError = {
 	status: number;
 	message: string;
+	/** Machine-readable `error.code` from the backend error envelope. */
+	code?: string;
 };
 
 export type SchedulerStatus = {
@@ -42,6 +45,29 @@ export type AppStatus = {
 	version: AppVersionStatus;
 };
 
+async function readApiError(res: Response): Promise<ApiError> {
+	const text = await res.text().catch(() => "");
+	try {
+		const parsed = JSON.parse(text) as {
+			error?: { code?: unknown; message?: unknown };
+		};
+		const error = parsed?.error;
+		if (error && typeof error === "object") {
+			return {
+				status: res.status,
+				message:
+					typeof error.message === "string" && error.message
+						? error.message
+						: res.statusText,
+				code: typeof error.code === "string" ? error.code : undefined,
+			};
+		}
+	} catch {
+		// Non-JSON bodies (static assets, webhooks) fall through as plain text.
+	}
+	return { status: res.status, message: text || res.statusText };
+}
+
 function isMockEnabled(): boolean {
 	if (import.meta.env.VITE_ENABLE_MOCKS === "true") {
 		return true;
@@ -112,7 +138,7 @@ export function ApiProvider({ children }: PropsWithChildren) {
 
 		const probe = async () => {
 			try {
-				const res = await fetch("/health");
+				const res = await fetch(withBase("/health"));
 				if (res.status === 401) {
 					handle401();
 					return;
@@ -137,7 +163,7 @@ export function ApiProvider({ children }: PropsWithChildren) {
 			};
 
 			try {
-				const res = await fetch("/api/settings");
+				const res = await fetch(withBase("/api/settings"));
 				if (res.status === 401) {
 					handle401();
 					return;
@@ -197,7 +223,7 @@ export function ApiProvider({ children }: PropsWithChildren) {
 		if (mockEnabled) {
 			(async () => {
 				try {
-					const res = await fetch("/sse/hello");
+					const res = await fetch(withBase("/sse/hello"));
 					if (cancelled) return;
 					setSseStatus(res.ok ? "open" : "error");
 				} catch {
@@ -209,7 +235,7 @@ export function ApiProvider({ children }: PropsWithChildren) {
 			};
 		}
 
-		const source = new EventSource("/sse/hello");
+		const source = new EventSource(withBase("/sse/hello"));
 
 		const onMessage = () => {
 			if (!cancelled) {
@@ -234,7 +260,7 @@ export function ApiProvider({ children }: PropsWithChildren) {
 
 	const getJson = useCallback(
 		async <T,>(input: RequestInfo | URL, init?: RequestInit): Promise<T> => {
-			const res = await fetch(input, {
+			const res = await fetch(resolveRequestInput(input), {
 				...init,
 				headers: {
 					Accept: "application/json",
@@ -251,11 +277,7 @@ export function ApiProvider({ children }: PropsWithChildren) {
 			}
 
 			if (!res.ok) {
-				const text = await res.text().catch(() => res.statusText);
-				throw {
-					status: res.status,
-					message: text || res.statusText,
-				} satisfies ApiError;
+				throw await readApiError(res);
 			}
 
 			return (await res.json()) as T;
@@ -276,7 +298,7 @@ export function ApiProvider({ children }: PropsWithChildren) {
 				...(init?.headers ?? {}),
 			};
 
-			const res = await fetch(input, {
+			const res = await fetch(resolveRequestInput(input), {
 				...init,
 				method: "POST",
 				headers,
@@ -292,11 +314,7 @@ export function ApiProvider({ children }: PropsWithChildren) {
 			}
 
 			if (!res.ok) {
-				const text = await res.text().catch(() => res.statusText);
-				throw {
-					status: res.status,
-					message: text || res.statusText,
-				} satisfies ApiError;
+				throw await readApiError(res);
 			}
 
 			return (await res.json()) as T;
@@ -5,6 +5,8 @@ export type VersionInfo = {
 	current?: { package?: string; releaseTag?: string | null };
 	latest?: { releaseTag?: string | null; publishedAt?: string | null };
 	hasUpdate?: boolean | null;
+	majorJump?: boolean;
+	warning?: string | null;
 	lastCheckedAt?: number | null;
 	loading: boolean;
 	error?: string | null;
@@ -14,8 +16,12 @@ type VersionCheckResponse = {
 	current?: { package?: string | null; release_tag?: string | null };
 	latest?: { release_tag?: string | null; published_at?: string | null };
 	has_update?: boolean | null;
+	major_jump?: boolean;
+	warning?: string | null;
 	checked_at?: number | null;
 	compare_reason?: string | null;
+	cached_at?: number | null;
+	stale?: boolean;
 };
 
 const ONE_HOUR_MS = 60 * 60 * 1000;
@@ -131,6 +137,8 @@ export function useVersionCheck(): VersionInfo {
 				},
 				hasUpdate:
 					typeof data.has_update === "boolean" ? data.has_update : null,
+				majorJump: data.major_jump === true,
+				warning: data.warning ?? null,
 				lastCheckedAt: checkedAt,
 				loading: false,
 				error: null,
@@ -192,6 +192,8 @@ const handlers = [
 				has_update: true,
 				checked_at: Math.floor(Date.now() / 1000),
 				compare_reason: "mock-forced-update",
+				cached_at: Math.floor(Date.now() / 1000),
+				stale: false,
 			},
 			{ headers: JSON_HEADERS },
 		);
@@ -339,6 +341,7 @@ const handlers = [
 			reason?: string | null;
 			path?: string | null;
 			is_long_running?: boolean;
+			timeout_secs?: number | null;
 		};
 
 		const body = (await request.json().catch(() => ({}))) as CreateTaskBody;
@@ -347,10 +350,16 @@ const handlers = [
 		const source =
 			(body.source as TaskDetailResponse["trigger"]["source"] | undefined) ??
 			"manual";
-		const units =
-			Array.isArray(body.units) && body.units.length > 0
-				? body.units
-				: ["unknown.unit"];
+		const units = Array.isArray(body.units) ? body.units : [];
+		if (
+			units.length === 0 &&
+			!(kind === "maintenance" || kind === "internal")
+		) {
+			return HttpResponse.json(
+				{ error: "at least one unit is required" },
+				{ status: 400 },
+			);
+		}
 
 		const task = runtime.createAdHocTask({
 			kind,
@@ -359,13 +368,16 @@ const handlers = [
 			caller: body.caller ?? null,
 			reason: body.reason ?? null,
 			path: body.path ?? null,
-			is_long_running: body.is_long_running ?? true,
+			is_long_running:
+				body.is_long_running ??
+				!(kind === "maintenance" || kind === "internal"),
 		});
 
 		return HttpResponse.json(
 			{
 				task_id: task.task_id,
 				is_long_running: task.is_long_running ?? false,
+				timeout_secs: body.timeout_secs ?? null,
 				kind: task.kind,
 				status: task.status,
 			},
@@ -970,6 +982,26 @@ const handlers = [
 		});
 	}),
 
+	...(["pause", "resume"] as const).map((op) =>
+		http.post(`/api/scheduler/${op}`, async ({ request }) => {
+			const url = new URL(request.url);
+			const failure = degradedGuard(url) || authGuard(url) || maybeFailure();
+			if (failure) return failure;
+			await withLatency();
+			const body = (await request.json().catch(() => ({}))) as {
+				reason?: string | null;
+			};
+			return HttpResponse.json(
+				{
+					paused: op === "pause",
+					reason: op === "pause" ? (body.reason ?? null) : null,
+					updated_at: Math.floor(Date.now() / 1000),
+				},
+				{ headers: JSON_HEADERS },
+			);
+		}),
+	),
+
 	http.post("/api/prune-state", async ({ request }) => {
 		const url = new URL(request.url);
 		const failure = degradedGuard(url) || authGuard(url) || maybeFailure();
@@ -2,12 +2,14 @@
 import { faker } from "@faker-js/faker";
 import cloneDeep from "lodash-es/cloneDeep";
 import type {
+	CommandMeta,
 	Task,
 	TaskLogEntry,
 	TaskSummaryCounts,
 	TaskTriggerMeta,
 	TaskUnitSummary,
 } from "../domain/tasks";
+import { commandOutput } from "../domain/tasks";
 
 export type MockProfile =
 	| "happy-path"
@@ -84,12 +86,21 @@ export type SettingsSnapshot = {
 		PODUP_STATE_DIR?: string;
 		PODUP_TOKEN_configured?: boolean;
 		PODUP_GH_WEBHOOK_SECRET_configured?: boolean;
+		PODUP_GITHUB_TOKEN_configured?: boolean;
 	};
 	scheduler: {
 		interval_secs?: number;
 		min_interval_secs?: number;
 		max_iterations?: number | null;
 		recent_events?: { iteration?: number | null }[];
+		state?: {
+			iteration: number;
+			last_tick_at: number;
+			next_tick_at: number | null;
+			last_tick_errored: boolean;
+			last_error: string | null;
+			alive: boolean;
+		} | null;
 	};
 	systemd: {
 		auto_update_unit?: string;
@@ -399,12 +410,21 @@ function buildSettings(now: number, profile: MockProfile): SettingsSnapshot {
 			PODUP_STATE_DIR: "/var/lib/podup",
 			PODUP_TOKEN_configured: true,
 			PODUP_GH_WEBHOOK_SECRET_configured: profile !== "auth-error",
+			PODUP_GITHUB_TOKEN_configured: false,
 		},
 		scheduler: {
 			interval_secs: 900,
 			min_interval_secs: 300,
 			max_iterations: null,
 			recent_events: [{ iteration: 84 }],
+			state: {
+				iteration: 84,
+				last_tick_at: Math.floor(Date.now() / 1000) - 120,
+				next_tick_at: Math.floor(Date.now() / 1000) + 780,
+				last_tick_errored: false,
+				last_error: null,
+				alive: true,
+			},
 		},
 		systemd: {
 			auto_update_unit: "podman-auto-update.service",
@@ -568,8 +588,8 @@ function buildTasks(
 					type: "command",
 					command: "podman pull ghcr.io/example/svc-alpha:main",
 					argv: ["podman", "pull", "ghcr.io/example/svc-alpha:main"],
-					stdout: "pulling from registry.example...\ncomplete",
-					stderr: "warning: using cached image layer metadata",
+					stdout_tail: "pulling from registry.example...\ncomplete",
+					stderr_tail: "warning: using cached image layer metadata",
 					exit: "exit=0",
 					units: ["svc-alpha.service", "svc-beta.service"],
 				},
@@ -585,8 +605,8 @@ function buildTasks(
 					type: "command",
 					command: "systemctl --user restart svc-alpha.service",
 					argv: ["systemctl", "--user", "restart", "svc-alpha.service"],
-					stdout: "restarted svc-alpha.service\nreloaded dependencies",
-					stderr: "",
+					stdout_tail: "restarted svc-alpha.service\nreloaded dependencies",
+					stderr_tail: "",
 					exit: "exit=0",
 					ok: ["svc-alpha.service", "svc-beta.service"],
 				},
@@ -609,8 +629,8 @@ function buildTasks(
 						"svc-alpha.service",
 						"--property=ActiveState,SubState",
 					],
-					stdout: "ActiveState=active\nSubState=running\n",
-					stderr: "",
+					stdout_tail: "ActiveState=active\nSubState=running\n",
+					stderr_tail: "",
 					exit: "exit=0",
 					unit: "svc-alpha.service",
 					purpose: "health-check",
@@ -701,8 +721,8 @@ function buildTasks(
 					type: "command",
 					command: "podman pull ghcr.io/example/svc-alpha:main",
 					argv: ["podman", "pull", "ghcr.io/example/svc-alpha:main"],
-					stdout: "pulling from registry.example...\ncomplete",
-					stderr: "",
+					stdout_tail: "pulling from registry.example...\ncomplete",
+					stderr_tail: "",
 					exit: "exit=0",
 					units: ["svc-alpha.service"],
 				},
@@ -718,8 +738,8 @@ function buildTasks(
 					type: "command",
 					command: "systemctl --user restart svc-alpha.service",
 					argv: ["systemctl", "--user", "restart", "svc-alpha.service"],
-					stdout: "restarted svc-alpha.service",
-					stderr: "",
+					stdout_tail: "restarted svc-alpha.service",
+					stderr_tail: "",
 					exit: "exit=0",
 					ok: ["svc-alpha.service"],
 				},
@@ -742,8 +762,8 @@ function buildTasks(
 						"svc-alpha.service",
 						"--property=ActiveState,SubState",
 					],
-					stdout: "ActiveState=active\nSubState=running\n",
-					stderr: "",
+					stdout_tail: "ActiveState=active\nSubState=running\n",
+					stderr_tail: "",
 					exit: "exit=0",
 					unit: "svc-alpha.service",
 					purpose: "health-check",
@@ -836,8 +856,8 @@ function buildTasks(
 					type: "command",
 					command: "systemctl --user restart svc-alpha.service",
 					argv: ["systemctl", "--user", "restart", "svc-alpha.service"],
-					stdout: "restarted svc-alpha.service",
-					stderr: "",
+					stdout_tail: "restarted svc-alpha.service",
+					stderr_tail: "",
 					exit: "exit=0",
 					ok: ["svc-alpha.service"],
 				},
@@ -860,8 +880,8 @@ function buildTasks(
 						"svc-alpha.service",
 						"--property=ActiveState,SubState",
 					],
-					stdout: "ActiveState=active\nSubState=running\n",
-					stderr: "",
+					stdout_tail: "ActiveState=active\nSubState=running\n",
+					stderr_tail: "",
 					exit: "exit=0",
 					unit: "svc-alpha.service",
 					purpose: "health-check",
@@ -1251,8 +1271,8 @@ function buildTasks(
 						"--filter",
 						"label=app=svc-gamma",
 					],
-					stdout: "deleted 3 unused layers",
-					stderr: "",
+					stdout_tail: "deleted 3 unused layers",
+					stderr_tail: "",
 					exit: "exit=0",
 					unit: "svc-gamma.service",
 				},
@@ -1275,8 +1295,8 @@ function buildTasks(
 						"--filter",
 						"label=app=svc-gamma",
 					],
-					stdout: "",
-					stderr: "error: mock image prune failure",
+					stdout_tail: "",
+					stderr_tail: "error: mock image prune failure",
 					exit: "exit=1",
 					unit: "svc-gamma.service",
 				},
@@ -1539,8 +1559,8 @@ function buildTasks(
 								type: "command",
 								command: "podman pull ghcr.io/example/svc-alpha:main",
 								argv: ["podman", "pull", "ghcr.io/example/svc-alpha:main"],
-								stdout: "pulling from registry.example...\ncomplete",
-								stderr: "warning: using cached image layer metadata",
+								stdout_tail: "pulling from registry.example...\ncomplete",
+								stderr_tail: "warning: using cached image layer metadata",
 								exit: "exit=0",
 								units: ["svc-alpha.service", "svc-beta.service"],
 							},
@@ -1555,8 +1575,8 @@ function buildTasks(
 								type: "command",
 								command: "systemctl --user restart svc-alpha.service",
 								argv: ["systemctl", "--user", "restart", "svc-alpha.service"],
-								stdout: "restarted svc-alpha.service\nreloaded dependencies",
-								stderr: "",
+								stdout_tail: "restarted svc-alpha.service\nreloaded dependencies",
+								stderr_tail: "",
 								exit: "exit=0",
 								ok: ["svc-alpha.service", "svc-beta.service"],
 							},
@@ -1948,8 +1968,8 @@ class RuntimeStore {
 					type: "command",
 					command: `podman pull ghcr.io/example/${unitBase}:main`,
 					argv: ["podman", "pull", `ghcr.io/example/${unitBase}:main`],
-					stdout: `pulling ghcr.io/example/${unitBase}:main from registry.example...`,
-					stderr: "",
+					stdout_tail: `pulling ghcr.io/example/${unitBase}:main from registry.example...`,
+					stderr_tail: "",
 					exit: "exit=0",
 					units: input.units,
 				},
@@ -1965,8 +1985,8 @@ class RuntimeStore {
 					type: "command",
 					command: `systemctl --user restart ${firstUnit}`,
 					argv: ["systemctl", "--user", "restart", firstUnit],
-					stdout: `restarted ${firstUnit}\nreloaded dependent units`,
-					stderr: "",
+					stdout_tail: `restarted ${firstUnit}\nreloaded dependent units`,
+					stderr_tail: "",
 					exit: "exit=0",
 					ok: input.units,
 				},
@@ -1986,12 +2006,12 @@ class RuntimeStore {
 				if (imagePullEntry) {
 					const meta = (
 						imagePullEntry.meta && typeof imagePullEntry.meta === "object"
-							? (imagePullEntry.meta as { stdout?: string })
-							: { stdout: undefined }
-					) as { stdout?: string };
-					const nextStdout = `${meta.stdout ?? ""}\nlayer download complete`;
+							? imagePullEntry.meta
+							: {}
+					) as CommandMeta;
+					const nextStdout = `${commandOutput(meta).stdout}\nlayer download complete`;
 					this.updateTaskLog(task_id, imagePullEntry.id, {
-						meta: { ...(imagePullEntry.meta as object), stdout: nextStdout },
+						meta: { ...meta, stdout_tail: nextStdout },
 					});
 				}
 			}, 800);
@@ -2005,16 +2025,16 @@ class RuntimeStore {
 				if (imagePullEntry) {
 					const meta = (
 						imagePullEntry.meta && typeof imagePullEntry.meta === "object"
-							? (imagePullEntry.meta as { stdout?: string })
-							: { stdout: undefined }
-					) as { stdout?: string };
-					const nextStdout = `${meta.stdout ?? ""}\nimage up to date`;
+							? imagePullEntry.meta
+							: {}
+					) as CommandMeta;
+					const nextStdout = `${commandOutput(meta).stdout}\nimage up to date`;
 					this.updateTaskLog(task_id, imagePullEntry.id, {
 						status: "succeeded",
 						meta: {
-							...(imagePullEntry.meta as object),
-							stdout: nextStdout,
-							stderr: "warning: using cached image layers metadata",
+							...meta,
+							stdout_tail: nextStdout,
+							stderr_tail: "warning: using cached image layers metadata",
 						},
 					});
 				}
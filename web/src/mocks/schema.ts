@@ -21,6 +21,7 @@ export const settingsSchema = z
 				PODUP_STATE_DIR: z.string().optional(),
 				PODUP_TOKEN_configured: z.boolean().optional(),
 				PODUP_GH_WEBHOOK_SECRET_configured: z.boolean().optional(),
+				PODUP_GITHUB_TOKEN_configured: z.boolean().optional(),
 			})
 			.passthrough(),
 		scheduler: z
@@ -28,6 +29,18 @@ export const settingsSchema = z
 				interval_secs: z.number().optional(),
 				min_interval_secs: z.number().optional(),
 				max_iterations: z.number().nullable().optional(),
+				state: z
+					.object({
+						iteration: z.number(),
+						last_tick_at: z.number(),
+						next_tick_at: z.number().nullable(),
+						last_tick_errored: z.boolean(),
+						last_error: z.string().nullable(),
+						alive: z.boolean(),
+					})
+					.passthrough()
+					.nullable()
+					.optional(),
 				recent_events: z
 					.array(
 						z
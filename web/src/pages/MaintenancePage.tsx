@@ -1,6 +1,7 @@
 import { Icon } from "@iconify/react";
 import { useEffect, useState } from "react";
 import { useNavigate } from "react-router-dom";
+import { withBase } from "../basePath";
 import { useToast } from "../components/Toast";
 import { useApi } from "../hooks/useApi";
 
@@ -94,7 +95,7 @@ export default function MaintenancePage() {
 	};
 
 	const downloadDebugPayload = () => {
-		window.location.href = "/last_payload.bin";
+		window.location.href = withBase("/last_payload.bin");
 	};
 
 	const dbStats = resources?.database_file;
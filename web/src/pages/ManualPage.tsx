@@ -2,6 +2,7 @@ import { Icon } from "@iconify/react";
 import type { FormEvent } from "react";
 import { useEffect, useState } from "react";
 import { useNavigate, useSearchParams } from "react-router-dom";
+import { withBase } from "../basePath";
 import { AutoUpdateWarningsBlock } from "../components/AutoUpdateWarningsBlock";
 import type { ManualServiceRowService } from "../components/manual/ManualServiceRow";
 import { ManualServicesCard } from "../components/manual/ManualServicesCard";
@@ -16,7 +17,7 @@ import type {
 	TaskStatus,
 	TasksListResponse,
 } from "../domain/tasks";
-import { isCommandMeta } from "../domain/tasks";
+import { commandOutput, isCommandMeta } from "../domain/tasks";
 import { useApi } from "../hooks/useApi";
 import { usePrefersReducedMotion } from "../hooks/usePrefersReducedMotion";
 import { type PresencePhase, usePresence } from "../hooks/usePresence";
@@ -776,7 +777,7 @@ function ManualTasksDrawer({
 		const url = `/sse/task-logs?task_id=${encodeURIComponent(selectedTaskId)}`;
 		let source: EventSource;
 		try {
-			source = new EventSource(url);
+			source = new EventSource(withBase(url));
 		} catch {
 			// 创建失败时静默降级为仅依赖 HTTP 轮询。
 			return;
@@ -1388,20 +1389,24 @@ function ManualTasksDrawer({
 																? log.meta
 																: null;
 
+															const commandStreams = commandMeta
+																? commandOutput(commandMeta)
+																: null;
+
 															const combinedLines =
-																commandMeta &&
-																(commandMeta.stdout || commandMeta.stderr)
+																commandStreams &&
+																(commandStreams.stdout || commandStreams.stderr)
 																	? [
-																			...(commandMeta.stdout
-																				? commandMeta.stdout
+																			...(commandStreams.stdout
+																				? commandStreams.stdout
 																						.split("\n")
 																						.map((text) => ({
 																							stream: "stdout" as const,
 																							text,
 																						}))
 																				: []),
-																			...(commandMeta.stderr
-																				? commandMeta.stderr
+																			...(commandStreams.stderr
+																				? commandStreams.stderr
 																						.split("\n")
 																						.map((text) => ({
 																							stream: "stderr" as const,
@@ -1,6 +1,7 @@
 import { Icon } from "@iconify/react";
 import { useEffect, useState } from "react";
 import { Link } from "react-router-dom";
+import { useToast } from "../components/Toast";
 import { useApi } from "../hooks/useApi";
 
 type SettingsResponse = {
@@ -8,11 +9,21 @@ type SettingsResponse = {
 		PODUP_STATE_DIR?: string;
 		PODUP_TOKEN_configured?: boolean;
 		PODUP_GH_WEBHOOK_SECRET_configured?: boolean;
+		PODUP_GITHUB_TOKEN_configured?: boolean;
 	};
 	scheduler: {
 		interval_secs?: number;
 		min_interval_secs?: number;
 		max_iterations?: number | null;
+		state?: {
+			iteration?: number;
+			last_tick_at?: number;
+			next_tick_at?: number | null;
+			last_tick_errored?: boolean;
+			last_error?: string | null;
+			alive?: boolean;
+		} | null;
+		pause?: SchedulerPause;
 	};
 	systemd: {
 		auto_update_unit?: string;
@@ -40,9 +51,17 @@ type SettingsResponse = {
 	};
 };
 
+type SchedulerPause = {
+	paused: boolean;
+	reason?: string | null;
+	updated_at?: number | null;
+};
+
 export default function SettingsPage() {
-	const { getJson } = useApi();
+	const { getJson, postJson } = useApi();
+	const { pushToast } = useToast();
 	const [settings, setSettings] = useState<SettingsResponse | null>(null);
+	const [pausePending, setPausePending] = useState(false);
 
 	useEffect(() => {
 		let cancelled = false;
@@ -61,6 +80,32 @@ export default function SettingsPage() {
 
 	const scheduler = settings?.scheduler;
 	const systemd = settings?.systemd;
+
+	const toggleSchedulerPause = async () => {
+		const pause = !scheduler?.pause?.paused;
+		setPausePending(true);
+		try {
+			const next = await postJson<SchedulerPause>(
+				pause ? "/api/scheduler/pause" : "/api/scheduler/resume",
+				{},
+			);
+			setSettings((prev) =>
+				prev ? { ...prev, scheduler: { ...prev.scheduler, pause: next } } : prev,
+			);
+		} catch (err) {
+			const message =
+				err && typeof err === "object" && "message" in err && err.message
+					? String(err.message)
+					: "Unknown error";
+			pushToast({
+				variant: "error",
+				title: pause ? "暂停调度器失败" : "恢复调度器失败",
+				message,
+			});
+		} finally {
+			setPausePending(false);
+		}
+	};
 	const forward = settings?.forward_auth;
 	const tasks = settings?.tasks;
 
@@ -96,6 +141,11 @@ export default function SettingsPage() {
 									configured={settings?.env.PODUP_GH_WEBHOOK_SECRET_configured}
 									secret
 								/>
+								<EnvRow
+									name="PODUP_GITHUB_TOKEN"
+									configured={settings?.env.PODUP_GITHUB_TOKEN_configured}
+									secret
+								/>
 							</tbody>
 						</table>
 					</div>
@@ -125,6 +175,60 @@ export default function SettingsPage() {
 										: "∞"}
 								</code>
 							</li>
+							<li>
+								Last tick:{" "}
+								<code>
+									{scheduler?.state?.last_tick_at
+										? new Date(
+												scheduler.state.last_tick_at * 1000,
+											).toLocaleString()
+										: "--"}
+								</code>
+								{scheduler?.state ? (
+									<span className="ml-1 text-base-content/60">
+										(#{scheduler.state.iteration ?? "--"}
+										{scheduler.state.last_tick_errored ? ", errored" : ""})
+									</span>
+								) : null}
+							</li>
+							<li>
+								Next tick:{" "}
+								<code>
+									{scheduler?.state?.next_tick_at
+										? new Date(
+												scheduler.state.next_tick_at * 1000,
+											).toLocaleString()
+										: "--"}
+								</code>
+								{scheduler?.state ? (
+									<span
+										className={`badge badge-xs ml-1 ${
+											scheduler.state.alive ? "badge-success" : "badge-warning"
+										}`}
+									>
+										{scheduler.state.alive ? "alive" : "stopped"}
+									</span>
+								) : null}
+							</li>
+							<li className="flex items-center gap-2">
+								<span>
+									Paused:{" "}
+									<code>{scheduler?.pause?.paused ? "yes" : "no"}</code>
+									{scheduler?.pause?.reason ? (
+										<span className="ml-1 text-base-content/60">
+											({scheduler.pause.reason})
+										</span>
+									) : null}
+								</span>
+								<button
+									type="button"
+									className="btn btn-xs"
+									onClick={toggleSchedulerPause}
+									disabled={pausePending || !settings}
+								>
+									{scheduler?.pause?.paused ? "Resume" : "Pause"}
+								</button>
+							</li>
 							<li>
 								Task retention (tasks table):{" "}
 								<code>{tasks?.task_retention_secs ?? "--"}</code> seconds
@@ -1,6 +1,7 @@
 import { Icon } from "@iconify/react";
 import { useEffect, useMemo, useState } from "react";
 import { Link, useSearchParams } from "react-router-dom";
+import { withBase } from "../basePath";
 import { AutoUpdateWarningsBlock } from "../components/AutoUpdateWarningsBlock";
 import { TaskLogActionLabel } from "../components/TaskLogActionLabel";
 import { TaskLogMetaDetails } from "../components/TaskLogMetaDetails";
@@ -14,7 +15,7 @@ import type {
 	TaskStatus,
 	TasksListResponse,
 } from "../domain/tasks";
-import { isCommandMeta } from "../domain/tasks";
+import { commandOutput, isCommandMeta } from "../domain/tasks";
 import { useApi } from "../hooks/useApi";
 import { usePrefersReducedMotion } from "../hooks/usePrefersReducedMotion";
 import { usePresence } from "../hooks/usePresence";
@@ -53,8 +54,8 @@ function buildMockCommandMetaFallback(
 			type: "command",
 			command: `podman pull ghcr.io/example/${mainService}:main`,
 			argv: ["podman", "pull", `ghcr.io/example/${mainService}:main`],
-			stdout: "pulling from registry.example...\ncomplete",
-			stderr: "warning: using cached image layer metadata",
+			stdout_tail: "pulling from registry.example...\ncomplete",
+			stderr_tail: "warning: using cached image layer metadata",
 			exit: "exit=0",
 			units,
 		};
@@ -65,8 +66,8 @@ function buildMockCommandMetaFallback(
 		type: "command",
 		command: `systemctl --user restart ${firstUnit}`,
 		argv: ["systemctl", "--user", "restart", firstUnit],
-		stdout: `restarted ${firstUnit}\nreloaded dependencies`,
-		stderr: "",
+		stdout_tail: `restarted ${firstUnit}\nreloaded dependencies`,
+		stderr_tail: "",
 		exit: "exit=0",
 		ok: units,
 	};
@@ -301,7 +302,7 @@ export default function TasksPage() {
 		const url = `/sse/task-logs?task_id=${encodeURIComponent(urlTaskId)}`;
 		let source: EventSource;
 		try {
-			source = new EventSource(url);
+			source = new EventSource(withBase(url));
 		} catch {
 			// 创建失败时静默降级为仅依赖 HTTP 轮询。
 			return;
@@ -664,6 +665,7 @@ export default function TasksPage() {
 								<option value="">全部</option>
 								<option value="running">running</option>
 								<option value="pending">pending</option>
+								<option value="queued">queued</option>
 								<option value="succeeded">succeeded</option>
 								<option value="anomaly">anomaly</option>
 								<option value="failed">failed</option>
@@ -794,6 +796,11 @@ export default function TasksPage() {
 														<span>{task.warning_count ?? 0}</span>
 													</span>
 												) : null}
+												{task.tags?.map((tag) => (
+													<span key={tag} className="badge badge-ghost badge-xs">
+														{tag}
+													</span>
+												))}
 											</div>
 										</td>
 										<td className="max-w-xs truncate text-[11px]">
@@ -1069,20 +1076,24 @@ export default function TasksPage() {
 																		mockEnabled,
 																	);
 
+															const commandStreams = commandMeta
+																? commandOutput(commandMeta)
+																: null;
+
 															const combinedLines =
-																commandMeta &&
-																(commandMeta.stdout || commandMeta.stderr)
+																commandStreams &&
+																(commandStreams.stdout || commandStreams.stderr)
 																	? [
-																			...(commandMeta.stdout
-																				? commandMeta.stdout
+																			...(commandStreams.stdout
+																				? commandStreams.stdout
 																						.split("\n")
 																						.map((text) => ({
 																							stream: "stdout" as const,
 																							text,
 																						}))
 																				: []),
-																			...(commandMeta.stderr
-																				? commandMeta.stderr
+																			...(commandStreams.stderr
+																				? commandStreams.stderr
 																						.split("\n")
 																						.map((text) => ({
 																							stream: "stderr" as const,